@@ -0,0 +1,450 @@
+//! # Basic Strategy
+//!
+//! This module contains `BasicStrategy`, a bot that plays the mathematically correct action
+//! for every hand. Its tables are not copied from a printed chart: they are computed from the
+//! table rules (decks, S17/H17, peek, doubling, DAS, splitting and surrender) when it is created.
+//!
+//! Each table cell holds the expected value of standing, hitting, doubling, splitting and
+//! surrendering, worked out for a fresh shoe with the dealer's up card (and, for pairs, both of
+//! the player's cards) removed. The dealer's odds are exact for that shoe (see `dealer_odds`);
+//! the player's draws use its card odds without further removal. Splits are valued without resplitting.
+
+use crate::{
+    card::Card,
+    dealer_odds::DealerOdds,
+    expected_value::{dealer_odds, split_hand_value, surrender_value, total},
+    game::Action,
+    hand::Hand,
+    round::Offer,
+    rules::TableRules,
+    shoe::Composition,
+    strategy::{Strategy, TableView},
+};
+
+/// Odds of drawing each card value: index 0 is the ace, 1 - 9 are the twos through ten-valued cards
+type Odds = [f64; 10];
+
+/// Index of a card value (2 - 11) in `Odds`
+fn index(value: u8) -> usize {
+    if value == 11 { 0 } else { value as usize - 1 }
+}
+
+/// Points the card at `Odds` index `i` adds to a hard total, counting aces as 1
+fn points(i: usize) -> u8 {
+    i as u8 + 1
+}
+
+/// Odds of drawing each card value from `unseen`
+fn odds(unseen: &Composition) -> Odds {
+    std::array::from_fn(|i| unseen.probability(card_value(i)))
+}
+
+/// Expected values of a player drawing from one shoe against one dealer up card
+struct Drawing {
+    odds: Odds,
+    dealer: DealerOdds,
+    /// Best of standing or hitting again for every hard total (aces as 1) with and without an ace
+    best: [[f64; 2]; 22],
+}
+
+impl Drawing {
+    /// Constructor: Works out the best of standing or hitting for every total, from 21 down
+    fn new(odds: Odds, dealer: DealerOdds) -> Self {
+        let mut drawing = Drawing {
+            odds,
+            dealer,
+            best: [[0.0; 2]; 22],
+        };
+
+        for hard in (2..=21).rev() {
+            for ace in [false, true] {
+                let best = drawing.stand(hard, ace).max(drawing.hit(hard, ace));
+                drawing.best[hard as usize][ace as usize] = best;
+            }
+        }
+
+        drawing
+    }
+
+    /// Expected value of standing
+    fn stand(&self, hard: u8, ace: bool) -> f64 {
+        self.dealer.stand_ev(total(hard, ace))
+    }
+
+    /// Expected value of hitting, then playing on by hitting or standing
+    fn hit(&self, hard: u8, ace: bool) -> f64 {
+        self.draw(hard, ace, |hard, ace| {
+            self.best[hard as usize][ace as usize]
+        })
+    }
+
+    /// Expected value of doubling: twice the bet, exactly one more card
+    fn double(&self, hard: u8, ace: bool) -> f64 {
+        2.0 * self.draw(hard, ace, |hard, ace| self.stand(hard, ace))
+    }
+
+    /// Expected value of one hand of a split pair of the card at `Odds` index `pair`
+    ///
+    /// The hand is dealt its second card and played on without resplitting
+    fn split_hand(&self, pair: usize, rules: &TableRules) -> f64 {
+        let aces = pair == 0;
+
+        self.odds
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                let hard = points(pair) + points(i);
+                let ace = aces || i == 0;
+
+                let ev = split_hand_value(rules, aces, total(hard, ace), |action| match action {
+                    Action::Stand => self.stand(hard, ace),
+                    Action::Double => self.double(hard, ace),
+                    _ => self.best[hard as usize][ace as usize],
+                });
+
+                p * ev
+            })
+            .sum()
+    }
+
+    /// Expected value of drawing one card, valuing each resulting hand with `then`
+    fn draw(&self, hard: u8, ace: bool, then: impl Fn(u8, bool) -> f64) -> f64 {
+        self.odds
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| {
+                let drawn = hard + points(i);
+
+                if drawn > 21 {
+                    -p
+                } else {
+                    p * then(drawn, ace || i == 0)
+                }
+            })
+            .sum()
+    }
+}
+
+/// Expected values of every action for one hand against one dealer up card, as a fraction of the bet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActionValues {
+    pub stand: f64,
+    pub hit: f64,
+    /// `None` if the rules don't allow doubling this hand
+    pub double: Option<f64>,
+    /// `None` unless the hand is a pair the rules allow splitting
+    pub split: Option<f64>,
+    /// `None` if the rules don't allow surrender
+    pub surrender: Option<f64>,
+}
+
+impl ActionValues {
+    /// Expected value of `action`, or `None` if it has no value for this hand
+    pub fn ev(&self, action: Action) -> Option<f64> {
+        match action {
+            Action::Stand => Some(self.stand),
+            Action::Hit => Some(self.hit),
+            Action::Double => self.double,
+            Action::Split => self.split,
+            Action::Surrender => self.surrender,
+            _ => None,
+        }
+    }
+
+    /// The action in `allowed` with the highest expected value, standing if none has a value
+    pub fn best(&self, allowed: &[Action]) -> Action {
+        allowed
+            .iter()
+            .filter_map(|&action| self.ev(action).map(|ev| (action, ev)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(Action::Stand, |(action, _)| action)
+    }
+}
+
+/// A bot playing basic strategy, with tables generated for one set of table rules
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicStrategy {
+    rules: TableRules,
+    /// Hard totals 4 - 21, by dealer up card
+    hard: Vec<[ActionValues; 10]>,
+    /// Soft totals 12 - 21, by dealer up card
+    soft: Vec<[ActionValues; 10]>,
+    /// Pairs of aces through ten-valued cards, by dealer up card
+    pairs: Vec<[ActionValues; 10]>,
+    /// Chance the dealer holds a blackjack under each up card, before any peek
+    blackjack: [f64; 10],
+}
+
+impl BasicStrategy {
+    /// Constructor: Generates the strategy tables for the given rules
+    pub fn new(rules: &TableRules) -> Self {
+        let mut strategy = BasicStrategy {
+            rules: rules.clone(),
+            hard: Vec::new(),
+            soft: Vec::new(),
+            pairs: Vec::new(),
+            blackjack: [0.0; 10],
+        };
+
+        let drawings: Vec<Drawing> = (0..10).map(|up| strategy.drawing(up, &[])).collect();
+
+        for up in 0..10 {
+            let unseen = strategy.unseen(up, &[]);
+            strategy.blackjack[up] = match up {
+                0 => unseen.probability(10),
+                9 => unseen.probability(11),
+                _ => 0.0,
+            };
+        }
+
+        strategy.hard = (4..=21)
+            .map(|hard| std::array::from_fn(|up| strategy.cell(&drawings[up], hard, false, None)))
+            .collect();
+        strategy.soft = (2..=11)
+            .map(|hard| std::array::from_fn(|up| strategy.cell(&drawings[up], hard, true, None)))
+            .collect();
+        strategy.pairs = (0..10)
+            .map(|pair| {
+                std::array::from_fn(|up| {
+                    let value = card_value(pair);
+                    let drawing = strategy.drawing(up, &[value, value]);
+
+                    strategy.cell(&drawing, 2 * points(pair), pair == 0, Some(pair))
+                })
+            })
+            .collect();
+
+        strategy
+    }
+
+    /// The rules these tables were generated for
+    pub fn rules(&self) -> &TableRules {
+        &self.rules
+    }
+
+    /// Expected values of every action for `hand` against the dealer's up card
+    pub fn values(&self, hand: &Hand, dealer_upcard: Card) -> ActionValues {
+        self.lookup(hand, dealer_upcard)
+    }
+
+    /// The best action for `hand` among the `allowed` ones
+    pub fn action(&self, hand: &Hand, dealer_upcard: Card, allowed: &[Action]) -> Action {
+        self.lookup(hand, dealer_upcard).best(allowed)
+    }
+
+    /// Check if surrendering `hand` before the dealer checks for blackjack beats playing it
+    pub fn surrender_early(&self, hand: &Hand, dealer_upcard: Card) -> bool {
+        if hand.is_blackjack() {
+            return false;
+        }
+
+        let values = self.lookup(hand, dealer_upcard);
+        let play = values
+            .ev(values.best(&[Action::Stand, Action::Hit, Action::Double, Action::Split]))
+            .unwrap_or(values.stand);
+
+        // Tables that peek value hands knowing the dealer has no blackjack
+        let play = if self.rules.hole_card {
+            let blackjack = self.blackjack[index(dealer_upcard.value())];
+            (1.0 - blackjack) * play - blackjack
+        } else {
+            play
+        };
+
+        play < -0.5
+    }
+
+    /// Find the table cell for `hand`
+    fn lookup(&self, hand: &Hand, dealer_upcard: Card) -> ActionValues {
+        let up = index(dealer_upcard.value());
+        let cards = &hand.cards;
+
+        if cards.len() == 2 && cards[0].value() == cards[1].value() {
+            self.pairs[index(cards[0].value())][up]
+        } else if hand.is_soft() {
+            self.soft[hand.value().clamp(12, 21) as usize - 12][up]
+        } else {
+            self.hard[hand.value().clamp(4, 21) as usize - 4][up]
+        }
+    }
+
+    /// Cards left in a fresh shoe once the up card at `Odds` index `up` and the `removed` values are dealt
+    fn unseen(&self, up: usize, removed: &[u8]) -> Composition {
+        let mut unseen = Composition::full(self.rules.decks);
+        unseen.remove_value(card_value(up));
+        for &value in removed {
+            unseen.remove_value(value);
+        }
+        unseen
+    }
+
+    /// Odds of the shoe and dealer once the up card at `Odds` index `up` and `removed` are dealt
+    fn drawing(&self, up: usize, removed: &[u8]) -> Drawing {
+        let unseen = self.unseen(up, removed);
+        let dealer = dealer_odds(card_value(up), &unseen, &self.rules);
+
+        Drawing::new(odds(&unseen), dealer)
+    }
+
+    /// Value every action for a hand worth `hard` (aces as 1)
+    fn cell(&self, drawing: &Drawing, hard: u8, ace: bool, pair: Option<usize>) -> ActionValues {
+        let rules = &self.rules;
+        let blackjack = drawing.dealer.blackjack;
+
+        ActionValues {
+            stand: drawing.stand(hard, ace),
+            hit: drawing.hit(hard, ace),
+            double: rules
+                .double
+                .allows(total(hard, ace))
+                .then(|| drawing.double(hard, ace)),
+            split: pair
+                .filter(|_| rules.max_split_hands >= 2)
+                .map(|pair| 2.0 * drawing.split_hand(pair, rules)),
+            surrender: surrender_value(rules, blackjack),
+        }
+    }
+}
+
+/// Card value (2 - 11) of the card at `Odds` index `i`
+fn card_value(i: usize) -> u8 {
+    if i == 0 { 11 } else { points(i) }
+}
+
+impl Strategy for BasicStrategy {
+    fn choose_bet(&mut self, table: &TableView) -> u32 {
+        table.rules.min_bet
+    }
+
+    fn choose_action(&mut self, hand: &Hand, dealer_upcard: Card, allowed: &[Action]) -> Action {
+        self.action(hand, dealer_upcard, allowed)
+    }
+
+    /// Surrenders early when that loses less than playing on, and never takes insurance or even money
+    fn choose_offer(
+        &mut self,
+        offer: Offer,
+        hand: &Hand,
+        dealer_upcard: Card,
+        _table: &TableView,
+    ) -> Action {
+        if offer == Offer::EarlySurrender && self.surrender_early(hand, dealer_upcard) {
+            Action::Surrender
+        } else {
+            Action::Decline
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card::parse_hand, rules::SurrenderRule};
+
+    /// The best action with every playing decision allowed
+    fn play(strategy: &BasicStrategy, cards: &str, upcard: &str) -> Action {
+        let all = [
+            Action::Stand,
+            Action::Hit,
+            Action::Double,
+            Action::Split,
+            Action::Surrender,
+        ];
+        strategy.action(&parse_hand(cards).unwrap(), upcard.parse().unwrap(), &all)
+    }
+
+    /// Test the generated tables
+    ///
+    /// Generates the Vegas Strip tables and asserts the plays every basic strategy chart agrees on
+    #[test]
+    fn test_basic_strategy_chart() {
+        let strategy = BasicStrategy::new(&TableRules::vegas_strip());
+
+        assert_eq!(play(&strategy, "TS 2H", "4C"), Action::Stand);
+        assert_eq!(play(&strategy, "TS 2H", "2C"), Action::Hit);
+        assert_eq!(play(&strategy, "TS 6H", "TC"), Action::Hit);
+        assert_eq!(play(&strategy, "TS 7H", "AC"), Action::Stand);
+        assert_eq!(play(&strategy, "6S 5H", "6C"), Action::Double);
+        assert_eq!(play(&strategy, "AS 7H", "9C"), Action::Hit);
+        assert_eq!(play(&strategy, "AS 6H", "5C"), Action::Double);
+        assert_eq!(play(&strategy, "8S 8H", "TC"), Action::Split);
+        assert_eq!(play(&strategy, "AS AH", "6C"), Action::Split);
+        assert_eq!(play(&strategy, "TS KH", "6C"), Action::Stand);
+        assert_eq!(play(&strategy, "5S 5H", "9C"), Action::Double);
+    }
+
+    /// Test that the tables follow the rules
+    ///
+    /// Asserts the plays that change with H17, late surrender and the European no-hole-card game
+    #[test]
+    fn test_rule_dependent_tables() {
+        let mut rules = TableRules::vegas_strip();
+        assert_eq!(
+            play(&BasicStrategy::new(&rules), "6S 5H", "AC"),
+            Action::Hit
+        );
+        rules.dealer_hits_soft_17 = true;
+        assert_eq!(
+            play(&BasicStrategy::new(&rules), "6S 5H", "AC"),
+            Action::Double
+        );
+
+        let atlantic = BasicStrategy::new(&TableRules::atlantic_city());
+        assert_eq!(play(&atlantic, "TS 6H", "TC"), Action::Surrender);
+        assert_eq!(play(&atlantic, "8S 8H", "TC"), Action::Split);
+
+        let european = BasicStrategy::new(&TableRules::european());
+        assert_eq!(play(&european, "8S 8H", "TC"), Action::Hit);
+        assert_eq!(play(&european, "6S 5H", "TC"), Action::Hit);
+        assert_eq!(play(&european, "AS 6H", "5C"), Action::Hit);
+    }
+
+    /// Test falling back when the best action isn't allowed
+    ///
+    /// Asserts 11 hits and soft 18 stands once doubling is off the table
+    #[test]
+    fn test_allowed_fallback() {
+        let strategy = BasicStrategy::new(&TableRules::vegas_strip());
+        let allowed = [Action::Hit, Action::Stand];
+
+        assert_eq!(
+            strategy.action(
+                &parse_hand("6S 5H").unwrap(),
+                "6C".parse().unwrap(),
+                &allowed
+            ),
+            Action::Hit
+        );
+        assert_eq!(
+            strategy.action(
+                &parse_hand("AS 7H").unwrap(),
+                "4C".parse().unwrap(),
+                &allowed
+            ),
+            Action::Stand
+        );
+        assert_eq!(
+            strategy.action(
+                &parse_hand("TS 6H").unwrap(),
+                "TC".parse().unwrap(),
+                &allowed
+            ),
+            Action::Hit
+        );
+    }
+
+    /// Test early surrender decisions
+    ///
+    /// Asserts 16 is surrendered early against an ace, but 20 and blackjack are not
+    #[test]
+    fn test_early_surrender() {
+        let mut rules = TableRules::vegas_strip();
+        rules.surrender = SurrenderRule::Early;
+        let strategy = BasicStrategy::new(&rules);
+        let ace: Card = "AC".parse().unwrap();
+
+        assert!(strategy.surrender_early(&parse_hand("TS 6H").unwrap(), ace));
+        assert!(!strategy.surrender_early(&parse_hand("TS QH").unwrap(), ace));
+        assert!(!strategy.surrender_early(&parse_hand("AS QH").unwrap(), ace));
+    }
+}
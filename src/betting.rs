@@ -0,0 +1,387 @@
+//! # Betting
+//!
+//! This module contains the `BetStrategy` trait, which sizes the bet of each round,
+//! and the classic betting systems: flat betting, a true count ramp, Martingale, Paroli
+//! and a Kelly fraction.
+//!
+//! A bet strategy only decides how much to bet. `Betting` pairs one with a playing
+//! `Strategy`, so any player can have their bets chosen for them.
+
+use crate::{
+    card::Card,
+    counting::{Counter, CountingSystem},
+    game::{Action, Game},
+    hand::Hand,
+    round::{Event, Offer},
+    rules::TableRules,
+    strategy::{Strategy, TableView},
+};
+
+/// Player advantage at a true count of 0, an estimate for typical multi-deck rules
+const BASE_ADVANTAGE: f64 = -0.005;
+
+/// Player advantage gained per true count point
+const ADVANTAGE_PER_COUNT: f64 = 0.005;
+
+/// Variance of a round of blackjack, in squared bets
+const ROUND_VARIANCE: f64 = 1.3;
+
+/// Consecutive wins a Paroli progression lets ride before starting over
+const PAROLI_STEPS: u32 = 3;
+
+/// A system for sizing bets
+pub trait BetStrategy {
+    /// Name of the system, as shown in reports
+    fn name(&self) -> &str;
+
+    /// Choose the bet for the next round, before it is made valid with `TableView::clamp_bet()`
+    fn bet(&mut self, table: &TableView) -> u32;
+
+    /// Called with the events of every step of the round, e.g. to count cards or track wins
+    fn observe(&mut self, _events: &[Event]) {}
+}
+
+impl<B: BetStrategy + ?Sized> BetStrategy for Box<B> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn bet(&mut self, table: &TableView) -> u32 {
+        (**self).bet(table)
+    }
+
+    fn observe(&mut self, events: &[Event]) {
+        (**self).observe(events);
+    }
+}
+
+/// Bet a fixed number of table minimums every round
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flat {
+    pub units: u32,
+}
+
+impl BetStrategy for Flat {
+    fn name(&self) -> &str {
+        "flat"
+    }
+
+    fn bet(&mut self, table: &TableView) -> u32 {
+        self.units * table.rules.min_bet
+    }
+}
+
+/// Bet one table minimum per true count point above 1, from 1 up to `spread` units
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountRamp {
+    pub counter: Counter,
+    pub spread: u32,
+}
+
+impl CountRamp {
+    /// Constructor: Ramps up to `spread` units, counting a shoe of `decks` decks with `system`
+    pub fn new(system: CountingSystem, decks: u8, spread: u32) -> Self {
+        CountRamp {
+            counter: Counter::new(system, decks),
+            spread,
+        }
+    }
+}
+
+impl BetStrategy for CountRamp {
+    fn name(&self) -> &str {
+        "ramp"
+    }
+
+    fn bet(&mut self, table: &TableView) -> u32 {
+        if table.shuffle_due {
+            self.counter.reset();
+        }
+
+        let units = (self.counter.true_count().floor() - 1.0).clamp(1.0, self.spread as f64);
+        units as u32 * table.rules.min_bet
+    }
+
+    fn observe(&mut self, events: &[Event]) {
+        self.counter.observe(events);
+    }
+}
+
+/// Double the bet after every loss, and go back to the table minimum after a win
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Martingale {
+    losses: u32,
+}
+
+impl BetStrategy for Martingale {
+    fn name(&self) -> &str {
+        "martingale"
+    }
+
+    fn bet(&mut self, table: &TableView) -> u32 {
+        table
+            .rules
+            .min_bet
+            .saturating_mul(2u32.saturating_pow(self.losses))
+    }
+
+    fn observe(&mut self, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::RoundOver { net, .. } if *net < 0 => self.losses += 1,
+                Event::RoundOver { net, .. } if *net > 0 => self.losses = 0,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Double the bet after every win, up to three wins in a row, and go back to the table minimum after a loss
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Paroli {
+    wins: u32,
+}
+
+impl BetStrategy for Paroli {
+    fn name(&self) -> &str {
+        "paroli"
+    }
+
+    fn bet(&mut self, table: &TableView) -> u32 {
+        table.rules.min_bet << self.wins
+    }
+
+    fn observe(&mut self, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::RoundOver { net, .. } if *net > 0 => {
+                    self.wins = (self.wins + 1) % PAROLI_STEPS;
+                }
+                Event::RoundOver { net, .. } if *net < 0 => self.wins = 0,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Bet a `fraction` of the Kelly bet for the advantage the true count gives, or the table minimum without one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kelly {
+    pub counter: Counter,
+    pub fraction: f64,
+}
+
+impl Kelly {
+    /// Constructor: Bets `fraction` of the Kelly bet, counting a shoe of `decks` decks with `system`
+    pub fn new(system: CountingSystem, decks: u8, fraction: f64) -> Self {
+        Kelly {
+            counter: Counter::new(system, decks),
+            fraction,
+        }
+    }
+
+    /// Estimated player advantage at the current true count
+    pub fn advantage(&self) -> f64 {
+        BASE_ADVANTAGE + ADVANTAGE_PER_COUNT * self.counter.true_count()
+    }
+}
+
+impl BetStrategy for Kelly {
+    fn name(&self) -> &str {
+        "kelly"
+    }
+
+    fn bet(&mut self, table: &TableView) -> u32 {
+        if table.shuffle_due {
+            self.counter.reset();
+        }
+
+        let kelly = table.bankroll as f64 * self.advantage() / ROUND_VARIANCE;
+        (self.fraction * kelly).max(0.0) as u32
+    }
+
+    fn observe(&mut self, events: &[Event]) {
+        self.counter.observe(events);
+    }
+}
+
+/// Returns the names accepted by `preset()`, one per betting system
+pub fn preset_names() -> &'static [&'static str] {
+    &["flat", "ramp", "martingale", "paroli", "kelly"]
+}
+
+/// Looks up a betting system by name, counting with `system` if it needs a count
+///
+/// The ramp spreads 1 to 8 units and Kelly bets half the Kelly bet. Returns `None` if no system matches
+pub fn preset(
+    name: &str,
+    rules: &TableRules,
+    system: &CountingSystem,
+) -> Option<Box<dyn BetStrategy>> {
+    let system = system.clone();
+
+    match name.trim().to_lowercase().as_str() {
+        "flat" => Some(Box::new(Flat { units: 1 })),
+        "ramp" | "count-ramp" => Some(Box::new(CountRamp::new(system, rules.decks, 8))),
+        "martingale" => Some(Box::new(Martingale::default())),
+        "paroli" => Some(Box::new(Paroli::default())),
+        "kelly" => Some(Box::new(Kelly::new(system, rules.decks, 0.5))),
+        _ => None,
+    }
+}
+
+/// Plays like `player`, with every bet chosen by `bets`
+///
+/// Bets are kept valid: at least the table minimum, and at most the table maximum and the bankroll
+#[derive(Debug, Clone, PartialEq)]
+pub struct Betting<P, B> {
+    pub player: P,
+    pub bets: B,
+}
+
+impl<P, B> Betting<P, B> {
+    /// Constructor: Pairs a playing strategy with a betting system
+    pub fn new(player: P, bets: B) -> Self {
+        Betting { player, bets }
+    }
+}
+
+impl<P: Strategy, B: BetStrategy> Strategy for Betting<P, B> {
+    fn choose_bet(&mut self, table: &TableView) -> u32 {
+        table.clamp_bet(self.bets.bet(table))
+    }
+
+    fn choose_action(&mut self, hand: &Hand, dealer_upcard: Card, allowed: &[Action]) -> Action {
+        self.player.choose_action(hand, dealer_upcard, allowed)
+    }
+
+    fn choose_offer(
+        &mut self,
+        offer: Offer,
+        hand: &Hand,
+        dealer_upcard: Card,
+        table: &TableView,
+    ) -> Action {
+        self.player.choose_offer(offer, hand, dealer_upcard, table)
+    }
+
+    fn observe(&mut self, game: &Game, events: &[Event]) {
+        self.bets.observe(events);
+        self.player.observe(game, events);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card::parse_cards, round::Seat, strategy::MimicDealer};
+
+    /// Table view for a bankroll at the given rules
+    fn table(rules: &TableRules, bankroll: u32) -> TableView<'_> {
+        TableView {
+            bankroll,
+            round: 1,
+            rules,
+            seed: 0,
+            shoe_remaining: 52 * rules.decks as usize,
+            shuffle_due: false,
+            bet: 0,
+            max_insurance: 0,
+        }
+    }
+
+    /// Event ending a round with the given net result
+    fn round_over(net: i64) -> Event {
+        Event::RoundOver { net, bankroll: 0 }
+    }
+
+    /// Test the progressions
+    ///
+    /// Feeds Martingale and Paroli a run of results and asserts the bets they make
+    #[test]
+    fn test_progressions() {
+        let rules = TableRules::vegas_strip();
+        let table = table(&rules, 10_000);
+        let mut martingale = Martingale::default();
+        let mut paroli = Paroli::default();
+        let mut bets = Vec::new();
+
+        for net in [-10, -20, 0, 40, 10, 10, 10, -10] {
+            bets.push((martingale.bet(&table), paroli.bet(&table)));
+            martingale.observe(&[round_over(net)]);
+            paroli.observe(&[round_over(net)]);
+        }
+
+        assert_eq!(
+            bets,
+            [
+                (10, 10),
+                (20, 10),
+                (40, 10),
+                (40, 10),
+                (10, 20),
+                (10, 40),
+                (10, 10),
+                (10, 20)
+            ]
+        );
+    }
+
+    /// Test the count-based bets
+    ///
+    /// Shows the ramp, a KO ramp and Kelly a run of low cards, asserts they raise their bets,
+    /// then asserts they start over when the shoe is due for a shuffle
+    #[test]
+    fn test_count_bets() {
+        let rules = TableRules::vegas_strip();
+        let mut view = table(&rules, 10_000);
+        let mut ramp = CountRamp::new(CountingSystem::hi_lo(), rules.decks, 8);
+        let mut kelly = Kelly::new(CountingSystem::hi_lo(), rules.decks, 0.5);
+        let mut ko_ramp = CountRamp::new(CountingSystem::ko(), rules.decks, 8);
+
+        assert_eq!(ramp.bet(&view), 10);
+        assert_eq!(kelly.bet(&view), 0);
+
+        let events: Vec<Event> = parse_cards("2C 3C 4C 5C 6C 2D 3D 4D 5D 6D 2H 3H 4H 5H 6H")
+            .unwrap()
+            .into_iter()
+            .map(|card| Event::CardDealt {
+                to: Seat::Dealer,
+                card,
+            })
+            .collect();
+        ramp.observe(&events);
+        kelly.observe(&events);
+        ko_ramp.observe(&events);
+
+        // 15 low cards with 3.7 decks left is a true count of about 4. KO counts them
+        // from -12 to +3, one short of its pivot, for a true count of about 3.7
+        assert_eq!(ramp.bet(&view), 30);
+        assert_eq!(ko_ramp.bet(&view), 20);
+        assert!(kelly.advantage() > 0.0);
+        assert!(kelly.bet(&view) > 50);
+
+        view.shuffle_due = true;
+        assert_eq!(ramp.bet(&view), 10);
+        assert_eq!(kelly.bet(&view), 0);
+    }
+
+    /// Test `Betting`
+    ///
+    /// Plays rounds with a Martingale bettor and asserts every bet is kept within the table limits
+    #[test]
+    fn test_betting_limits() {
+        let mut rules = TableRules::vegas_strip();
+        rules.max_bet = 80;
+        let mut game = Game::with_seed(1000, rules, 11);
+        let mut player = Betting::new(MimicDealer, Martingale::default());
+
+        for _ in 0..50 {
+            if game.bankroll < game.rules.min_bet {
+                break;
+            }
+            game.play_round(&mut player).unwrap();
+            assert!(game.bet >= game.rules.min_bet && game.bet <= game.rules.max_bet);
+        }
+    }
+}
@@ -2,11 +2,15 @@
 //!
 //! This module contains the `Card`, `Suit`, and `Rank` structs/enums
 //! for representing playing cards in blackjack.
+//!
+//! With the `serde` feature, all three serialize as their plain-text codes: `"AS"`, `"S"` and `"A"`.
 
+use crate::hand::Hand;
 use colored::*;
-use std::fmt;
+use std::{error::Error, fmt, str::FromStr};
 
 /// Define the four suits in a standard deck of cards
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Suit {
     HEARTS,
@@ -20,6 +24,16 @@ impl Suit {
     pub fn all() -> &'static [Suit] {
         &[Suit::HEARTS, Suit::DIAMONDS, Suit::CLUBS, Suit::SPADES]
     }
+
+    /// Returns the plain-text letter for the suit: `H`, `D`, `C` or `S`
+    pub fn letter(&self) -> char {
+        match self {
+            Suit::HEARTS => 'H',
+            Suit::DIAMONDS => 'D',
+            Suit::CLUBS => 'C',
+            Suit::SPADES => 'S',
+        }
+    }
 }
 
 impl fmt::Display for Suit {
@@ -35,6 +49,7 @@ impl fmt::Display for Suit {
 }
 
 /// Define the ranks in a standard deck of cards
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Rank {
     TWO,
@@ -133,6 +148,16 @@ impl Card {
         &self.rank
     }
 
+    /// Returns a reference to the suit of the card
+    pub fn suit(&self) -> &Suit {
+        &self.suit
+    }
+
+    /// Returns the plain-text code for the card, e.g. `AS` or `TD`, as parsed by `from_str()`
+    pub fn code(&self) -> String {
+        format!("{}{}", self.rank, self.suit.letter())
+    }
+
     /// Returns the integer value of the card
     pub fn value(&self) -> u8 {
         self.rank.value()
@@ -144,3 +169,166 @@ impl fmt::Display for Card {
         write!(f, "{}{}", self.rank, self.suit)
     }
 }
+
+/// Error returned when a card code like `AS` or `TD` can't be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseCardError(String);
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid card '{}'", self.0)
+    }
+}
+
+impl Error for ParseCardError {}
+
+/// Parses a rank code: `2`-`9`, `T` or `10`, `J`, `Q`, `K` or `A`, case-insensitive
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "2" => Ok(Rank::TWO),
+            "3" => Ok(Rank::THREE),
+            "4" => Ok(Rank::FOUR),
+            "5" => Ok(Rank::FIVE),
+            "6" => Ok(Rank::SIX),
+            "7" => Ok(Rank::SEVEN),
+            "8" => Ok(Rank::EIGHT),
+            "9" => Ok(Rank::NINE),
+            "T" | "10" => Ok(Rank::TEN),
+            "J" => Ok(Rank::JACK),
+            "Q" => Ok(Rank::QUEEN),
+            "K" => Ok(Rank::KING),
+            "A" => Ok(Rank::ACE),
+            _ => Err(ParseCardError(s.to_string())),
+        }
+    }
+}
+
+/// Parses a suit letter: `S`, `H`, `D` or `C`, case-insensitive
+impl FromStr for Suit {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "H" => Ok(Suit::HEARTS),
+            "D" => Ok(Suit::DIAMONDS),
+            "C" => Ok(Suit::CLUBS),
+            "S" => Ok(Suit::SPADES),
+            _ => Err(ParseCardError(s.to_string())),
+        }
+    }
+}
+
+/// Parses a card code: a rank (`2`-`9`, `T` or `10`, `J`, `Q`, `K`, `A`)
+/// followed by a suit (`S`, `H`, `D`, `C`), case-insensitive
+impl FromStr for Card {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim();
+        let err = |_| ParseCardError(s.to_string());
+
+        let split = code
+            .char_indices()
+            .last()
+            .ok_or(ParseCardError(s.to_string()))?
+            .0;
+        let (rank, suit) = code.split_at(split);
+
+        Ok(Card::new(
+            suit.parse().map_err(err)?,
+            rank.parse().map_err(err)?,
+        ))
+    }
+}
+
+/// Serializes a type as its plain-text code, and deserializes it with `FromStr`
+#[cfg(feature = "serde")]
+macro_rules! serde_as_code {
+    ($t:ty, $code:expr) => {
+        impl serde::Serialize for $t {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(&$code(self))
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $t {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let code = String::deserialize(deserializer)?;
+                code.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+serde_as_code!(Card, Card::code);
+#[cfg(feature = "serde")]
+serde_as_code!(Suit, Suit::letter);
+#[cfg(feature = "serde")]
+serde_as_code!(Rank, Rank::to_string);
+
+/// Parse a whitespace-separated list of card codes, e.g. `"AS KH 5D"`, keeping their order
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, ParseCardError> {
+    s.split_whitespace().map(str::parse).collect()
+}
+
+/// Parse a whitespace-separated list of card codes into a hand holding them, e.g. `"AS 7H"`
+pub fn parse_hand(s: &str) -> Result<Hand, ParseCardError> {
+    let mut hand = Hand::new();
+    for card in parse_cards(s)? {
+        hand.add_card(card);
+    }
+    Ok(hand)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test card code parsing
+    ///
+    /// Parses codes in upper and lower case, including `10`, and asserts the resulting cards
+    #[test]
+    fn test_parse_card() {
+        assert_eq!("AS".parse(), Ok(Card::new(Suit::SPADES, Rank::ACE)));
+        assert_eq!("td".parse(), Ok(Card::new(Suit::DIAMONDS, Rank::TEN)));
+        assert_eq!("10H".parse(), Ok(Card::new(Suit::HEARTS, Rank::TEN)));
+
+        assert!("1S".parse::<Card>().is_err());
+        assert!("KX".parse::<Card>().is_err());
+        assert!("".parse::<Card>().is_err());
+    }
+
+    /// Test `parse_cards()` and `code()`
+    ///
+    /// Parses a list of cards and asserts that converting back with `code()` round-trips
+    #[test]
+    fn test_parse_cards() {
+        let cards = parse_cards("AS KH 5d  9C").unwrap();
+        let codes: Vec<String> = cards.iter().map(Card::code).collect();
+
+        assert_eq!(codes, vec!["AS", "KH", "5D", "9C"]);
+        assert!(parse_cards("AS ZZ").is_err());
+    }
+
+    /// Test serde card codes
+    ///
+    /// Serializes a card, suit and rank to JSON as codes, reads them back,
+    /// and asserts an unknown code is refused
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_codes() {
+        let card = Card::new(Suit::DIAMONDS, Rank::TEN);
+
+        assert_eq!(serde_json::to_string(&card).unwrap(), "\"TD\"");
+        assert_eq!(serde_json::to_string(&Suit::SPADES).unwrap(), "\"S\"");
+        assert_eq!(serde_json::to_string(&Rank::ACE).unwrap(), "\"A\"");
+
+        let cards: Vec<Card> = serde_json::from_str(r#"["AS", "td", "10H"]"#).unwrap();
+        assert_eq!(cards, parse_cards("AS TD TH").unwrap());
+        assert!(serde_json::from_str::<Card>("\"ZZ\"").is_err());
+    }
+}
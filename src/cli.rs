@@ -0,0 +1,359 @@
+//! # Command Line
+//!
+//! Parses the arguments passed to the binary into an `Options` struct: the command to run
+//! (play at the terminal, `simulate`, `bets` or `replay`) and its flags.
+//! Invalid arguments print a message and exit, since there is nothing to play without them.
+
+use crate::profile::Profile;
+use blackjack::{CountingSystem, TableRules, betting};
+use std::{path::PathBuf, process, thread};
+
+/// Rounds the `simulate` command plays unless `--rounds` is given
+const DEFAULT_ROUNDS: u64 = 100_000;
+
+/// Sessions the `bets` command plays per betting system unless `--sessions` is given
+const DEFAULT_SESSIONS: u64 = 1_000;
+
+/// Most rounds of a `bets` session unless `--rounds` is given
+const DEFAULT_SESSION_ROUNDS: u64 = 1_000;
+
+/// Bankroll every `bets` session starts with unless `--bankroll` is given
+const DEFAULT_BANKROLL: u32 = 1_000;
+
+/// What the binary was asked to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Play at the terminal (the default)
+    Play,
+    /// Play `rounds` rounds on `threads` threads without any terminal I/O, then report the results
+    Simulate { rounds: u64, threads: usize },
+    /// Play `sessions` sessions of up to `rounds` rounds from `bankroll` with every betting system,
+    /// on `threads` threads, then report the risk of ruin and growth of each
+    Bets {
+        rounds: u64,
+        sessions: u64,
+        bankroll: u32,
+        threads: usize,
+    },
+    /// Step through the hand history at `path`, or the latest one if not given
+    Replay { path: Option<PathBuf> },
+}
+
+/// Who makes the decisions at the table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayerKind {
+    /// The user, prompted at the terminal
+    Human,
+    /// The `MimicDealer` bot, which plays like the dealer
+    MimicDealer,
+    /// The `BasicStrategy` bot, which plays the best action for the table rules
+    BasicStrategy,
+}
+
+impl PlayerKind {
+    /// Names accepted by `--player`
+    pub fn names() -> &'static [&'static str] {
+        &["human", "mimic-dealer", "basic-strategy"]
+    }
+
+    /// The name `--player` accepts for this player
+    pub fn name(&self) -> &'static str {
+        match self {
+            PlayerKind::Human => "human",
+            PlayerKind::MimicDealer => "mimic-dealer",
+            PlayerKind::BasicStrategy => "basic-strategy",
+        }
+    }
+
+    /// Look up a player by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "human" => Some(PlayerKind::Human),
+            "mimic-dealer" | "dealer" => Some(PlayerKind::MimicDealer),
+            "basic-strategy" | "basic" => Some(PlayerKind::BasicStrategy),
+            _ => None,
+        }
+    }
+}
+
+/// Everything the user can configure from the command line
+pub struct Options {
+    pub command: Command,
+    pub rules: TableRules,
+    /// True if `--rules` or `--seed` was given, asking for a new table instead of the saved session
+    pub new_table: bool,
+    /// Seed for shuffling the shoe; a random one is picked if not given
+    pub seed: Option<u64>,
+    /// Who plays: the user when playing, basic strategy when simulating
+    pub player: PlayerKind,
+    /// Coach the human player with basic strategy hints and an accuracy score
+    pub coach: bool,
+    /// Show the exact expected value of each action next to the hands
+    pub show_ev: bool,
+    /// Train counting with this system, quizzing the player on the running count.
+    /// Count-based betting systems count with it too (Hi-Lo if not given)
+    pub count: Option<CountingSystem>,
+    /// Betting system choosing every bet instead of the player, by name
+    pub bets: Option<String>,
+    /// Profile to play as; the player is asked for one if not given
+    pub profile: Option<String>,
+    /// True if any of `--coach`, `--show-ev`, `--count` or `--bets` was given,
+    /// replacing the modes the profile played with last time
+    pub preferences_given: bool,
+}
+
+impl Options {
+    /// Parse `std::env::args()`, exiting with a usage message on bad input
+    pub fn from_args() -> Self {
+        let mut options = Options {
+            command: Command::Play,
+            rules: TableRules::default(),
+            new_table: false,
+            seed: None,
+            player: PlayerKind::Human,
+            coach: false,
+            show_ev: false,
+            count: None,
+            bets: None,
+            profile: None,
+            preferences_given: false,
+        };
+        let mut player = None;
+        let mut rounds = None;
+        let mut threads = None;
+        let mut sessions = None;
+        let mut bankroll = None;
+        let mut args = std::env::args().skip(1).peekable();
+
+        match args.peek().map(String::as_str) {
+            Some("simulate") => {
+                args.next();
+                options.command = Command::Simulate {
+                    rounds: DEFAULT_ROUNDS,
+                    threads: 1,
+                };
+            }
+            Some("bets") => {
+                args.next();
+                options.command = Command::Bets {
+                    rounds: DEFAULT_SESSION_ROUNDS,
+                    sessions: DEFAULT_SESSIONS,
+                    bankroll: DEFAULT_BANKROLL,
+                    threads: 1,
+                };
+            }
+            Some("replay") => {
+                args.next();
+                let path = args.next_if(|arg| !arg.starts_with('-'));
+                options.command = Command::Replay {
+                    path: path.map(PathBuf::from),
+                };
+            }
+            _ => {}
+        }
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--rules" => {
+                    let name = args.next().unwrap_or_default();
+
+                    options.rules = match TableRules::preset(&name) {
+                        Some(rules) => rules,
+                        None => exit_with_usage(&format!("Unknown rules preset '{}'", name)),
+                    };
+                    options.new_table = true;
+                }
+                "--seed" => {
+                    let seed = args.next().unwrap_or_default();
+
+                    options.seed = match seed.parse() {
+                        Ok(seed) => Some(seed),
+                        Err(_) => exit_with_usage(&format!("Invalid seed '{}'", seed)),
+                    };
+                    options.new_table = true;
+                }
+                "--player" => {
+                    let name = args.next().unwrap_or_default();
+
+                    player = match PlayerKind::from_name(&name) {
+                        Some(player) => Some(player),
+                        None => exit_with_usage(&format!("Unknown player '{}'", name)),
+                    };
+                }
+                "--rounds" => {
+                    let count = args.next().unwrap_or_default();
+
+                    rounds = match count.parse() {
+                        Ok(count) if count > 0 => Some(count),
+                        _ => exit_with_usage(&format!("Invalid number of rounds '{}'", count)),
+                    };
+                }
+                "--threads" => {
+                    let count = args.next().unwrap_or_default();
+
+                    threads = match count.parse() {
+                        Ok(count) if count > 0 => Some(count),
+                        _ => exit_with_usage(&format!("Invalid number of threads '{}'", count)),
+                    };
+                }
+                "--count" => {
+                    let name = args.next().unwrap_or_default();
+
+                    options.count = match CountingSystem::preset(&name) {
+                        Some(system) => Some(system),
+                        None => exit_with_usage(&format!("Unknown counting system '{}'", name)),
+                    };
+                    options.preferences_given = true;
+                }
+                "--bets" => {
+                    let name = args.next().unwrap_or_default();
+
+                    if betting::preset(&name, &options.rules, &CountingSystem::hi_lo()).is_none() {
+                        exit_with_usage(&format!("Unknown betting system '{}'", name));
+                    }
+                    options.bets = Some(name);
+                    options.preferences_given = true;
+                }
+                "--profile" => {
+                    let name = args.next().unwrap_or_default();
+
+                    if !Profile::is_valid_name(&name) {
+                        exit_with_usage(&format!(
+                            "Invalid profile name '{}' (up to 16 letters, digits, - or _)",
+                            name
+                        ));
+                    }
+                    options.profile = Some(name);
+                }
+                "--sessions" => {
+                    let count = args.next().unwrap_or_default();
+
+                    sessions = match count.parse() {
+                        Ok(count) if count > 0 => Some(count),
+                        _ => exit_with_usage(&format!("Invalid number of sessions '{}'", count)),
+                    };
+                }
+                "--bankroll" => {
+                    let amount = args.next().unwrap_or_default();
+
+                    bankroll = match amount.parse() {
+                        Ok(amount) if amount > 0 => Some(amount),
+                        _ => exit_with_usage(&format!("Invalid bankroll '{}'", amount)),
+                    };
+                }
+                "--coach" => {
+                    options.coach = true;
+                    options.preferences_given = true;
+                }
+                "--show-ev" => {
+                    options.show_ev = true;
+                    options.preferences_given = true;
+                }
+                "-h" | "--help" => {
+                    println!("{}", usage());
+                    process::exit(0);
+                }
+                _ => exit_with_usage(&format!("Unknown argument '{}'", arg)),
+            }
+        }
+
+        let all_threads = || thread::available_parallelism().map_or(1, |count| count.get());
+
+        if !matches!(options.command, Command::Bets { .. })
+            && (sessions.is_some() || bankroll.is_some())
+        {
+            exit_with_usage("--sessions and --bankroll only apply to the bets command");
+        }
+        if matches!(
+            options.command,
+            Command::Simulate { .. } | Command::Bets { .. }
+        ) && options.profile.is_some()
+        {
+            exit_with_usage("--profile only applies to playing and replay");
+        }
+
+        match options.command {
+            Command::Play | Command::Replay { .. } if rounds.is_some() || threads.is_some() => {
+                exit_with_usage(
+                    "--rounds and --threads only apply to the simulate and bets commands",
+                )
+            }
+            Command::Play | Command::Replay { .. } => {
+                options.player = player.unwrap_or(PlayerKind::Human);
+
+                if options.profile.is_some() && options.player != PlayerKind::Human {
+                    exit_with_usage("--profile needs the human player");
+                }
+            }
+            Command::Simulate { .. } => {
+                options.player = player.unwrap_or(PlayerKind::BasicStrategy);
+                options.command = Command::Simulate {
+                    rounds: rounds.unwrap_or(DEFAULT_ROUNDS),
+                    threads: threads.unwrap_or_else(all_threads),
+                };
+
+                if options.player == PlayerKind::Human {
+                    exit_with_usage("simulate needs a bot player");
+                }
+            }
+            Command::Bets { .. } => {
+                options.player = player.unwrap_or(PlayerKind::BasicStrategy);
+                options.command = Command::Bets {
+                    rounds: rounds.unwrap_or(DEFAULT_SESSION_ROUNDS),
+                    sessions: sessions.unwrap_or(DEFAULT_SESSIONS),
+                    bankroll: bankroll.unwrap_or(DEFAULT_BANKROLL),
+                    threads: threads.unwrap_or_else(all_threads),
+                };
+
+                if options.player == PlayerKind::Human {
+                    exit_with_usage("bets needs a bot player");
+                }
+            }
+        }
+
+        options
+    }
+}
+
+/// Usage message listing every flag
+fn usage() -> String {
+    format!(
+        "Usage: rust-blackjack-cli [--profile <name>] [--rules <preset>] [--seed <number>] [--player <name>] [--coach] [--show-ev] [--count <system>] [--bets <system>]\n       \
+         rust-blackjack-cli simulate [--rounds <number>] [--threads <number>] [--rules <preset>] [--seed <number>] [--player <name>]\n       \
+         rust-blackjack-cli bets [--sessions <number>] [--rounds <number>] [--bankroll <amount>] [--threads <number>] [--rules <preset>] [--seed <number>] [--player <name>] [--count <system>]\n       \
+         rust-blackjack-cli replay [<file>] [--profile <name>]\n\n\
+         Commands:\n  \
+         simulate            Play many rounds with a bot, no prompts, and report the expected value\n  \
+         bets                Play bankroll sessions with every betting system and report risk of ruin and growth\n  \
+         replay              Step through a recorded hand history (default: the profile's latest session)\n\n\
+         Options:\n  \
+         --profile <name>    Play as a named profile, with its own session, hand histories, lifetime stats\n                      \
+         and modes (--coach, --show-ev, --count, --bets are remembered until others are given)\n  \
+         --rules <preset>    Table rules: {}\n  \
+         --seed <number>     Shuffle seed, to replay a session exactly\n  \
+         --player <name>     Who plays: {}\n  \
+         --coach             Hints on ?, flag basic strategy mistakes and keep score\n  \
+         --show-ev           Show the exact expected value of each action next to the hands\n  \
+         --count <system>    Practice counting, quizzed every few rounds, and count for ramp and kelly bets: {}\n  \
+         --bets <system>     Bet with a betting system instead of being asked: {}\n  \
+         --rounds <number>   Rounds to simulate (default {}), or most rounds per bets session (default {})\n  \
+         --sessions <number> Sessions per betting system (default {})\n  \
+         --bankroll <amount> Bankroll each bets session starts with (default ${})\n  \
+         --threads <number>  Threads to simulate on (default: all cores)\n  \
+         -h, --help          Print this message",
+        TableRules::preset_names().join(", "),
+        PlayerKind::names().join(", "),
+        CountingSystem::preset_names().join(", "),
+        betting::preset_names().join(", "),
+        DEFAULT_ROUNDS,
+        DEFAULT_SESSION_ROUNDS,
+        DEFAULT_SESSIONS,
+        DEFAULT_BANKROLL
+    )
+}
+
+/// Print an error and the usage message, then exit
+fn exit_with_usage(msg: &str) -> ! {
+    eprintln!("{}\n\n{}", msg, usage());
+    process::exit(2);
+}
@@ -0,0 +1,165 @@
+//! # Coach
+//!
+//! Compares the player's decisions with basic strategy for the `--coach` mode,
+//! and keeps the session's accuracy score.
+
+use crate::score::Score;
+use blackjack::{Action, BasicStrategy, Card, Hand, Offer, TableRules};
+
+/// Keeps score of how often the player's decisions match basic strategy
+pub struct Coach {
+    strategy: BasicStrategy,
+    score: Score,
+}
+
+impl Coach {
+    /// Constructor: Coaches basic strategy for the given table rules
+    pub fn new(rules: &TableRules) -> Self {
+        Coach {
+            strategy: BasicStrategy::new(rules),
+            score: Score::default(),
+        }
+    }
+
+    /// Basic strategy's action for `hand` among the `allowed` ones
+    pub fn recommend(&self, hand: &Hand, dealer_upcard: Card, allowed: &[Action]) -> Action {
+        self.strategy.action(hand, dealer_upcard, allowed)
+    }
+
+    /// Basic strategy's answer to an offer: never insure, and surrender early only when it pays
+    pub fn recommend_offer(&self, offer: Offer, hand: &Hand, dealer_upcard: Card) -> Action {
+        if offer == Offer::EarlySurrender && self.strategy.surrender_early(hand, dealer_upcard) {
+            Action::Surrender
+        } else {
+            Action::Decline
+        }
+    }
+
+    /// Score a decision against the recommended one. Returns true if it matched
+    pub fn grade(&mut self, chosen: Action, recommended: Action) -> bool {
+        self.score.record(chosen == recommended)
+    }
+
+    /// Decisions that matched basic strategy, out of the decisions made
+    pub fn score(&self) -> Score {
+        self.score
+    }
+}
+
+/// Advice for a playing decision, e.g. `Basic strategy says double 11 vs 6`
+pub fn advice(action: Action, hand: &Hand, dealer_upcard: Card) -> String {
+    let verb = match action {
+        Action::Hit => "hit",
+        Action::Stand => "stand",
+        Action::Double => "double",
+        Action::Split => "split",
+        Action::Surrender => "surrender",
+        _ => "play",
+    };
+
+    format!(
+        "Basic strategy says {} {} vs {}",
+        verb,
+        describe(hand),
+        rank_label(dealer_upcard)
+    )
+}
+
+/// Advice for an offer, e.g. `Basic strategy says never take insurance`
+pub fn offer_advice(offer: Offer, action: Action, hand: &Hand, dealer_upcard: Card) -> String {
+    match (offer, action) {
+        (Offer::EarlySurrender, Action::Surrender) => advice(action, hand, dealer_upcard),
+        (Offer::EarlySurrender, _) => format!(
+            "Basic strategy says play {} vs {} out",
+            describe(hand),
+            rank_label(dealer_upcard)
+        ),
+        (Offer::EvenMoney, _) => "Basic strategy says never take even money".to_string(),
+        (Offer::Insurance, _) => "Basic strategy says never take insurance".to_string(),
+    }
+}
+
+/// Describe a hand the way strategy charts do: `11`, `soft 18` or `pair of 8s`
+fn describe(hand: &Hand) -> String {
+    let cards = &hand.cards;
+
+    if cards.len() == 2 && cards[0].value() == cards[1].value() {
+        match cards[0].value() {
+            11 => "pair of aces".to_string(),
+            value => format!("pair of {}s", value),
+        }
+    } else if hand.is_soft() {
+        format!("soft {}", hand.value())
+    } else {
+        hand.value().to_string()
+    }
+}
+
+/// The rank as it appears on a strategy chart: `2` - `10` or `A`
+fn rank_label(card: Card) -> String {
+    match card.value() {
+        11 => "A".to_string(),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blackjack::parse_hand;
+
+    /// Test coaching advice
+    ///
+    /// Asserts the recommendation and wording for 11 vs 6, soft 18 vs 9 and a pair of aces
+    #[test]
+    fn test_advice() {
+        let coach = Coach::new(&TableRules::vegas_strip());
+        let all = [Action::Hit, Action::Stand, Action::Double, Action::Split];
+        let six: Card = "6C".parse().unwrap();
+
+        let eleven = parse_hand("6S 5H").unwrap();
+        let action = coach.recommend(&eleven, six, &all);
+        assert_eq!(action, Action::Double);
+        assert_eq!(
+            advice(action, &eleven, six),
+            "Basic strategy says double 11 vs 6"
+        );
+
+        let soft = parse_hand("AS 7H").unwrap();
+        let nine: Card = "9C".parse().unwrap();
+        assert_eq!(
+            advice(coach.recommend(&soft, nine, &all), &soft, nine),
+            "Basic strategy says hit soft 18 vs 9"
+        );
+
+        let aces = parse_hand("AS AH").unwrap();
+        let ace: Card = "AC".parse().unwrap();
+        assert_eq!(
+            advice(coach.recommend(&aces, ace, &all), &aces, ace),
+            "Basic strategy says split pair of aces vs A"
+        );
+    }
+
+    /// Test the accuracy score
+    ///
+    /// Grades four decisions, two of them deviations, and asserts the score
+    #[test]
+    fn test_accuracy() {
+        let mut coach = Coach::new(&TableRules::default());
+        assert_eq!(coach.score().accuracy(), None);
+
+        assert!(coach.grade(Action::Hit, Action::Hit));
+        assert!(!coach.grade(Action::Stand, Action::Double));
+        assert!(!coach.grade(Action::Insurance(5), Action::Decline));
+        assert!(coach.grade(Action::Decline, Action::Decline));
+
+        assert_eq!(
+            coach.score(),
+            Score {
+                correct: 2,
+                total: 4
+            }
+        );
+        assert_eq!(coach.score().accuracy(), Some(50.0));
+    }
+}
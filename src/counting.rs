@@ -0,0 +1,284 @@
+//! # Counting
+//!
+//! Card counting systems and a `Counter` that keeps the running and true count of a shoe.
+//!
+//! A counter follows the game through its events: every `CardDealt` and `HoleCardRevealed`
+//! is counted, and a `Shuffled` starts the count over. When the shoe is reshuffled in the
+//! middle of a round, the cards on the table stay out of it, so they are counted again.
+
+use crate::{card::Card, round::Event};
+
+/// A card counting system: the tag it gives each card value
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountingSystem {
+    pub name: String,
+    /// Tag of each card value, from 2 through 10, then the ace
+    pub tags: [i8; 10],
+}
+
+impl CountingSystem {
+    /// Constructor: A custom system from the tags of the card values 2 through 10, then the ace
+    pub fn new(name: &str, tags: [i8; 10]) -> Self {
+        CountingSystem {
+            name: name.to_string(),
+            tags,
+        }
+    }
+
+    /// Preset: Hi-Lo, the classic balanced level 1 count
+    pub fn hi_lo() -> Self {
+        CountingSystem::new("Hi-Lo", [1, 1, 1, 1, 1, 0, 0, 0, -1, -1])
+    }
+
+    /// Preset: Knock-Out, an unbalanced level 1 count that counts 7s as low cards
+    pub fn ko() -> Self {
+        CountingSystem::new("KO", [1, 1, 1, 1, 1, 1, 0, 0, -1, -1])
+    }
+
+    /// Preset: Omega II, a balanced level 2 count that leaves aces out
+    pub fn omega_ii() -> Self {
+        CountingSystem::new("Omega II", [1, 1, 2, 2, 2, 1, 0, -1, -2, 0])
+    }
+
+    /// Preset: Zen Count, a balanced level 2 count
+    pub fn zen() -> Self {
+        CountingSystem::new("Zen", [1, 1, 2, 2, 2, 1, 0, 0, -2, -1])
+    }
+
+    /// Looks up a preset by name, e.g. `"hi-lo"`, `"ko"`, `"omega-ii"` or `"zen"`
+    ///
+    /// Returns `None` if no preset matches
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace(['_', ' '], "-").as_str() {
+            "hi-lo" | "hilo" => Some(CountingSystem::hi_lo()),
+            "ko" | "knock-out" => Some(CountingSystem::ko()),
+            "omega-ii" | "omega-2" | "omega" => Some(CountingSystem::omega_ii()),
+            "zen" => Some(CountingSystem::zen()),
+            _ => None,
+        }
+    }
+
+    /// Returns the names accepted by `preset()`, one per preset
+    pub fn preset_names() -> &'static [&'static str] {
+        &["hi-lo", "ko", "omega-ii", "zen"]
+    }
+
+    /// Tag of a card
+    pub fn tag(&self, card: &Card) -> i8 {
+        self.tags[card.value() as usize - 2]
+    }
+
+    /// Sum of the tags over a full deck; 0 for a balanced system
+    pub fn deck_total(&self) -> i32 {
+        // Four of every value, and sixteen ten-valued cards
+        self.tags
+            .iter()
+            .enumerate()
+            .map(|(i, &tag)| tag as i32 * if i == 8 { 16 } else { 4 })
+            .sum()
+    }
+
+    /// Check if the tags of a full deck add up to 0
+    pub fn is_balanced(&self) -> bool {
+        self.deck_total() == 0
+    }
+
+    /// Pivot of the system: the running count that gives the same true count at any depth
+    ///
+    /// 0 for balanced systems; for unbalanced ones, one deck's imbalance, like KO's +4
+    pub fn pivot(&self) -> i32 {
+        self.deck_total()
+    }
+
+    /// Running count to start a shoe of `decks` decks from
+    ///
+    /// 0 for balanced systems. Unbalanced ones start low enough to end the shoe on
+    /// one deck's imbalance, like KO's `4 - 4 × decks`
+    pub fn initial_count(&self, decks: u8) -> i32 {
+        -self.deck_total() * (decks as i32 - 1)
+    }
+}
+
+/// Keeps the count of a shoe as its cards are seen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Counter {
+    system: CountingSystem,
+    decks: u8,
+    running: i32,
+    seen: u32,
+    /// Cards counted since the round started
+    table: Vec<Card>,
+}
+
+impl Counter {
+    /// Constructor: Counts a freshly shuffled shoe of `decks` decks with `system`
+    pub fn new(system: CountingSystem, decks: u8) -> Self {
+        Counter {
+            running: system.initial_count(decks),
+            system,
+            decks,
+            seen: 0,
+            table: Vec::new(),
+        }
+    }
+
+    /// Returns the counting system in use
+    pub fn system(&self) -> &CountingSystem {
+        &self.system
+    }
+
+    /// Start over for a freshly shuffled shoe
+    pub fn reset(&mut self) {
+        self.running = self.system.initial_count(self.decks);
+        self.seen = 0;
+    }
+
+    /// Count a card that was seen
+    pub fn count(&mut self, card: &Card) {
+        self.running += self.system.tag(card) as i32;
+        self.seen += 1;
+    }
+
+    /// Count every card revealed by `events`, starting over when the shoe was shuffled
+    ///
+    /// A shuffle in the middle of a round leaves the cards on the table out of the new shoe,
+    /// so the count starts over from them
+    pub fn observe(&mut self, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::Shuffled => {
+                    self.reset();
+                    for card in self.table.clone() {
+                        self.count(&card);
+                    }
+                }
+                Event::CardDealt { card, .. } | Event::HoleCardRevealed(card) => {
+                    self.count(card);
+                    self.table.push(*card);
+                }
+                Event::RoundStarted { .. } | Event::RoundOver { .. } => self.table.clear(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Sum of the tags of the cards counted, from the initial count
+    pub fn running_count(&self) -> i32 {
+        self.running
+    }
+
+    /// Number of cards counted since the shuffle
+    pub fn cards_seen(&self) -> u32 {
+        self.seen
+    }
+
+    /// Decks not seen yet, never less than one card's worth
+    pub fn decks_remaining(&self) -> f64 {
+        let remaining = (self.decks as u32 * 52).saturating_sub(self.seen).max(1);
+        remaining as f64 / 52.0
+    }
+
+    /// Running count per deck remaining
+    ///
+    /// An unbalanced system is measured from its pivot, so KO's running count of +4
+    /// is a true count of +4 anywhere in the shoe
+    pub fn true_count(&self) -> f64 {
+        let pivot = self.system.pivot();
+        pivot as f64 + (self.running - pivot) as f64 / self.decks_remaining()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        card::parse_cards, game::Game, rules::TableRules, shoe::Composition, strategy::MimicDealer,
+    };
+
+    /// Test the counting systems
+    ///
+    /// Asserts which presets are balanced, KO's initial count, and the counts of a few cards
+    #[test]
+    fn test_counting_systems() {
+        for name in CountingSystem::preset_names() {
+            let system = CountingSystem::preset(name).unwrap();
+            assert_eq!(system.is_balanced(), system.name != "KO");
+        }
+        assert_eq!(CountingSystem::ko().initial_count(6), -20);
+        assert_eq!(CountingSystem::hi_lo().initial_count(6), 0);
+        assert_eq!(Counter::new(CountingSystem::ko(), 6).true_count(), 0.0);
+
+        let cards = parse_cards("2C 5D 7H 9S TC KD AS").unwrap();
+        let mut hi_lo = Counter::new(CountingSystem::hi_lo(), 1);
+        let mut zen = Counter::new(CountingSystem::zen(), 1);
+        for card in &cards {
+            hi_lo.count(card);
+            zen.count(card);
+        }
+
+        assert_eq!(hi_lo.running_count(), -1);
+        assert_eq!(zen.running_count(), -1);
+        assert_eq!(hi_lo.cards_seen(), 7);
+        assert!((hi_lo.true_count() + 52.0 / 45.0).abs() < 1e-12);
+    }
+
+    /// Test counting from game events
+    ///
+    /// Plays many rounds at a hole card and a no-hole-card table, and asserts after every
+    /// round that each card gone from the shoe was counted, hole cards included
+    #[test]
+    fn test_counting_events() {
+        for rules in [TableRules::vegas_strip(), TableRules::european()] {
+            let decks = rules.decks;
+            let mut game = Game::with_seed(1_000_000, rules, 3);
+            let mut counter = Counter::new(CountingSystem::hi_lo(), decks);
+
+            for _ in 0..500 {
+                let events = game.play_round(&mut MimicDealer).unwrap();
+                counter.observe(&events);
+
+                assert_eq!(
+                    counter.cards_seen() + game.unseen_cards().total(),
+                    decks as u32 * 52
+                );
+            }
+        }
+    }
+
+    /// Test counting through a reshuffle in the middle of a round
+    ///
+    /// Deals a single deck down to its last card so it runs dry mid-round, and asserts after
+    /// every round that the count matches the cards gone from the new shoe, including the
+    /// ones left on the table when it was reshuffled
+    #[test]
+    fn test_counting_mid_round_shuffle() {
+        let rules = TableRules {
+            penetration: 1.0,
+            ..TableRules::default()
+        };
+        let system = CountingSystem::ko();
+        let mut game = Game::with_seed(100_000, rules, 4);
+        let mut counter = Counter::new(system.clone(), 1);
+        let mut mid_round = 0;
+
+        for _ in 0..100 {
+            let events = game.play_round(&mut MimicDealer).unwrap();
+            if events.iter().skip(1).any(|event| *event == Event::Shuffled) {
+                mid_round += 1;
+            }
+            counter.observe(&events);
+
+            let unseen = game.unseen_cards();
+            let full = Composition::full(1);
+            let gone: i32 = (2..=11)
+                .map(|value| {
+                    let tag = system.tags[value as usize - 2] as i32;
+                    tag * (full.count(value) - unseen.count(value)) as i32
+                })
+                .sum();
+            assert_eq!(counter.running_count(), system.initial_count(1) + gone);
+            assert_eq!(counter.cards_seen() + unseen.total(), 52);
+        }
+        assert!(mid_round > 0);
+    }
+}
@@ -0,0 +1,346 @@
+//! # Dealer Odds
+//!
+//! This module computes the exact probabilities of the dealer finishing on 17, 18, 19, 20, 21,
+//! blackjack or bust, given the up card, the table rules and the cards left in the shoe.
+//!
+//! Every possible sequence of dealer draws is followed, taking each card out of the shoe as it
+//! is drawn, so the numbers are exact for the given composition rather than an estimate.
+
+use std::collections::HashMap;
+
+use crate::{card::Card, rules::TableRules, shoe::Composition};
+
+/// Card values in drawing order: two through ten, then the ace
+pub(crate) const VALUES: [u8; 10] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+/// Probabilities of how the dealer's hand finishes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DealerOdds {
+    /// Standing on 17, 18, 19, 20 and 21 (without a blackjack)
+    pub totals: [f64; 5],
+    pub blackjack: f64,
+    pub bust: f64,
+}
+
+impl DealerOdds {
+    /// Constructor: Exact odds for a dealer showing `upcard`, drawing the hole card and any
+    /// further cards from `unseen`, the cards the player hasn't seen (not including `upcard`)
+    ///
+    /// Assumes `unseen` holds enough cards to finish the hand
+    pub fn new(upcard: Card, unseen: &Composition, rules: &TableRules) -> Self {
+        DealerOdds::with_upcard_value(upcard.value(), unseen, rules.dealer_hits_soft_17)
+    }
+
+    /// Like `new()`, for an up card worth `up` (2 - 11)
+    pub(crate) fn with_upcard_value(up: u8, unseen: &Composition, hits_soft_17: bool) -> Self {
+        let mut odds = DealerOdds::default();
+        let mut memo = HashMap::new();
+        let cards = unseen.total() as f64;
+
+        for hole in VALUES {
+            let count = unseen.count(hole);
+            if count == 0 {
+                continue;
+            }
+
+            let p = count as f64 / cards;
+            if up + hole == 21 {
+                odds.blackjack += p;
+                continue;
+            }
+
+            let mut rest = *unseen;
+            rest.remove_value(hole);
+
+            let hard = hard(up) + hard(hole);
+            let drawn =
+                DealerOdds::draw(hard, up == 11 || hole == 11, rest, hits_soft_17, &mut memo);
+            odds.add(&drawn, p);
+        }
+
+        odds
+    }
+
+    /// Chance of finishing on `total` (17 - 21, not counting blackjack)
+    pub fn total(&self, total: u8) -> f64 {
+        match total {
+            17..=21 => self.totals[total as usize - 17],
+            _ => 0.0,
+        }
+    }
+
+    /// The odds once the dealer has peeked and is known not to hold a blackjack
+    pub fn given_no_blackjack(&self) -> Self {
+        let scale = 1.0 - self.blackjack;
+        if scale <= 0.0 {
+            return DealerOdds::default();
+        }
+
+        DealerOdds {
+            totals: self.totals.map(|p| p / scale),
+            blackjack: 0.0,
+            bust: self.bust / scale,
+        }
+    }
+
+    /// Expected value of a player standing on `total` (without a blackjack), as a fraction of the bet
+    pub fn stand_ev(&self, total: u8) -> f64 {
+        if total > 21 {
+            return -1.0;
+        }
+
+        let mut ev = self.bust - self.blackjack;
+        for (dealer, &p) in (17..=21).zip(&self.totals) {
+            if total > dealer {
+                ev += p;
+            } else if total < dealer {
+                ev -= p;
+            }
+        }
+        ev
+    }
+
+    /// Odds of a dealer hand worth `hard` (aces as 1) finishing, drawing from `unseen`
+    ///
+    /// For one up card, the cards left in `unseen` tell exactly which cards the dealer holds,
+    /// so they are the key for `memo`
+    fn draw(
+        hard: u8,
+        ace: bool,
+        unseen: Composition,
+        hits_soft_17: bool,
+        memo: &mut HashMap<Composition, DealerOdds>,
+    ) -> DealerOdds {
+        let mut odds = DealerOdds::default();
+        let value = if ace && hard + 10 <= 21 {
+            hard + 10
+        } else {
+            hard
+        };
+        let soft = value != hard;
+
+        if value > 21 {
+            odds.bust = 1.0;
+            return odds;
+        }
+        if value > 17 || (value == 17 && !(soft && hits_soft_17)) {
+            odds.totals[value as usize - 17] = 1.0;
+            return odds;
+        }
+        if let Some(odds) = memo.get(&unseen) {
+            return *odds;
+        }
+
+        let cards = unseen.total() as f64;
+        for card in VALUES {
+            let count = unseen.count(card);
+            if count == 0 {
+                continue;
+            }
+
+            let mut rest = unseen;
+            rest.remove_value(card);
+
+            let drawn = DealerOdds::draw(
+                hard + self::hard(card),
+                ace || card == 11,
+                rest,
+                hits_soft_17,
+                memo,
+            );
+            odds.add(&drawn, count as f64 / cards);
+        }
+
+        memo.insert(unseen, odds);
+        odds
+    }
+
+    /// Add `other`, weighted by probability `p`
+    fn add(&mut self, other: &DealerOdds, p: f64) {
+        for (total, other) in self.totals.iter_mut().zip(other.totals) {
+            *total += p * other;
+        }
+        self.blackjack += p * other.blackjack;
+        self.bust += p * other.bust;
+    }
+}
+
+/// Points a card value adds to a hard total, counting aces as 1
+pub(crate) fn hard(value: u8) -> u8 {
+    if value == 11 { 1 } else { value }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        expected_value::dealer_odds,
+        game::{Action, Game},
+        round::Phase,
+    };
+
+    /// Sum of every outcome's probability
+    fn sum(odds: &DealerOdds) -> f64 {
+        odds.totals.iter().sum::<f64>() + odds.blackjack + odds.bust
+    }
+
+    /// Plays a round of `game`, declining offers and standing, and returns the dealer's odds of
+    /// each outcome as the player's turn started, with the outcome the dealer finished on:
+    /// 17 - 21, then blackjack, then bust. `None` if the round ended before the player's turn
+    fn stand_round(game: &mut Game) -> Option<([f64; 7], usize)> {
+        game.apply(Action::Bet(10)).unwrap();
+        while game.phase() == Phase::Dealing {
+            game.apply(Action::Decline).unwrap();
+        }
+        if game.phase() != Phase::PlayerTurn {
+            return None;
+        }
+
+        let upcard = game.dealer_hand.cards[0].value();
+        let odds = dealer_odds(upcard, &game.unseen_cards(), &game.rules);
+        game.apply(Action::Stand).unwrap();
+
+        let dealer = &game.dealer_hand;
+        let outcome = match dealer.value() {
+            _ if dealer.is_blackjack() => 5,
+            value if value > 21 => 6,
+            value => value as usize - 17,
+        };
+        let [t17, t18, t19, t20, t21] = odds.totals;
+
+        Some((
+            [t17, t18, t19, t20, t21, odds.blackjack, odds.bust],
+            outcome,
+        ))
+    }
+
+    /// Test dealer odds from a full shoe
+    ///
+    /// Asserts the outcomes add up to 1, and the well-known single-deck numbers:
+    /// 16/51 blackjack under an ace and about 42% bust under a six
+    #[test]
+    fn test_full_shoe_odds() {
+        let rules = TableRules::default();
+
+        for up in ["2S", "6S", "TS", "AS"] {
+            let upcard: Card = up.parse().unwrap();
+            let mut unseen = Composition::full(1);
+            unseen.remove(&upcard);
+
+            let odds = DealerOdds::new(upcard, &unseen, &rules);
+            assert!((sum(&odds) - 1.0).abs() < 1e-9);
+        }
+
+        let ace: Card = "AS".parse().unwrap();
+        let mut unseen = Composition::full(1);
+        unseen.remove(&ace);
+        let odds = DealerOdds::new(ace, &unseen, &rules);
+        assert!((odds.blackjack - 16.0 / 51.0).abs() < 1e-12);
+        assert!((sum(&odds.given_no_blackjack()) - 1.0).abs() < 1e-9);
+
+        let six: Card = "6S".parse().unwrap();
+        let mut unseen = Composition::full(1);
+        unseen.remove(&six);
+        let odds = DealerOdds::new(six, &unseen, &rules);
+        assert!((odds.bust - 0.42).abs() < 0.01);
+        assert_eq!(odds.blackjack, 0.0);
+    }
+
+    /// Test dealer odds from a tiny, known shoe
+    ///
+    /// Dealer shows a ten over a shoe of one six and one ace: the hole card is the ace
+    /// (blackjack) or the six, after which the ace makes 17. Asserts both halves exactly,
+    /// and that H17 makes no difference to a hard 17
+    #[test]
+    fn test_exact_composition() {
+        let unseen = Composition::from_cards(&crate::card::parse_cards("6S AH").unwrap());
+        let ten: Card = "TC".parse().unwrap();
+
+        let mut rules = TableRules::default();
+        let odds = DealerOdds::new(ten, &unseen, &rules);
+
+        assert_eq!(odds.blackjack, 0.5);
+        assert_eq!(odds.total(17), 0.5);
+        assert_eq!(odds.bust, 0.0);
+
+        rules.dealer_hits_soft_17 = true;
+        assert_eq!(DealerOdds::new(ten, &unseen, &rules), odds);
+    }
+
+    /// Test H17 against S17
+    ///
+    /// Hitting soft 17 makes the dealer finish on 17 less often and bust more often
+    #[test]
+    fn test_hits_soft_17() {
+        let mut rules = TableRules::vegas_strip();
+        let upcard: Card = "6S".parse().unwrap();
+        let mut unseen = Composition::full(rules.decks);
+        unseen.remove(&upcard);
+
+        let s17 = DealerOdds::new(upcard, &unseen, &rules);
+        rules.dealer_hits_soft_17 = true;
+        let h17 = DealerOdds::new(upcard, &unseen, &rules);
+
+        assert!(h17.total(17) < s17.total(17));
+        assert!(h17.bust > s17.bust);
+        assert!((sum(&h17) - 1.0).abs() < 1e-9);
+    }
+
+    /// Test the odds against hands dealt by `Game`
+    ///
+    /// Plays rounds at single-deck S17, H17 and no-hole-card tables, and asserts how often the
+    /// dealer finishes on each total, with a blackjack, or bust lands within four standard errors
+    /// of the odds worked out at the start of each player's turn
+    #[test]
+    fn test_against_dealt_hands() {
+        let h17 = TableRules {
+            dealer_hits_soft_17: true,
+            ..TableRules::default()
+        };
+        let no_hole_card = TableRules {
+            hole_card: false,
+            ..TableRules::default()
+        };
+
+        for rules in [TableRules::default(), h17, no_hole_card] {
+            let mut game = Game::with_seed(1_000_000, rules, 17);
+            // Observed count, expected count and variance of each outcome
+            let mut outcomes = [(0.0, 0.0, 0.0); 7];
+
+            for _ in 0..2500 {
+                let Some((odds, outcome)) = stand_round(&mut game) else {
+                    continue;
+                };
+
+                for (i, (tally, p)) in outcomes.iter_mut().zip(odds).enumerate() {
+                    tally.0 += (i == outcome) as u32 as f64;
+                    tally.1 += p;
+                    tally.2 += p * (1.0 - p);
+                }
+            }
+
+            for (observed, expected, variance) in outcomes {
+                assert!((observed - expected).abs() <= 4.0 * f64::sqrt(variance));
+            }
+        }
+    }
+
+    /// Test the odds against a soft 17 dealt by `Game`
+    ///
+    /// Stacks a dealer A-6 with only sixes left, so the odds are certain, and asserts the dealer
+    /// stands on 17 at an S17 table and draws to 19 at an H17 table, as the odds say
+    #[test]
+    fn test_soft_17_against_game() {
+        for (hits_soft_17, total) in [(false, 17), (true, 19)] {
+            let rules = TableRules {
+                dealer_hits_soft_17: hits_soft_17,
+                ..TableRules::default()
+            };
+            let mut game = Game::scripted(rules, "TH AS 9C 6D 6C 6H 6S");
+
+            let (odds, outcome) = stand_round(&mut game).unwrap();
+            assert_eq!(game.dealer_hand.value(), total);
+            assert_eq!(odds[outcome], 1.0);
+        }
+    }
+}
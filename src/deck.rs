@@ -8,6 +8,7 @@ use rand::Rng;
 
 /// Represents a deck containing playing cards
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Deck {
     pub cards: Vec<Card>,
 }
@@ -25,14 +26,26 @@ impl Deck {
         Deck { cards }
     }
 
+    /// Constructor: Creates a stacked deck that deals `cards` in the given order, first card first
+    pub fn from_cards(mut cards: Vec<Card>) -> Self {
+        cards.reverse();
+
+        Deck { cards }
+    }
+
     /// Shuffle the deck using the Fisher-Yates algorithm
     ///
     /// Fisher-Yates algorithm randomly shuffles the deck in place
     /// by iterating backwards through the deck and swapping each card
     /// with another card at a random index less than or equal to the current index.
     pub fn shuffle(&mut self) {
-        let mut rng = rand::rng();
+        self.shuffle_with(&mut rand::rng());
+    }
 
+    /// Shuffle the deck with Fisher-Yates, drawing randomness from the given `rng`
+    ///
+    /// A seeded `rng` always produces the same order, which makes shuffles reproducible
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         for i in (1..self.cards.len()).rev() {
             let n = rng.random_range(0..=i);
 
@@ -40,20 +53,24 @@ impl Deck {
         }
     }
 
-    /// First creates new shuffled deck if needed, then removes and returns the top card if available.
+    /// Removes and returns the top card, or `None` once the deck is empty
+    ///
+    /// The deck never rebuilds itself; reshuffling between rounds is the job of `Shoe`
     pub fn deal(&mut self) -> Option<Card> {
-        if self.cards.len() == 20 {
-            let new_deck = Deck::new();
-            self.cards = new_deck.cards;
-            self.shuffle();
-        }
         self.cards.pop()
     }
 }
 
+impl Default for Deck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{SeedableRng, rngs::StdRng};
 
     /// Test `Deck` initialization
     ///
@@ -67,23 +84,35 @@ mod tests {
 
     /// Test `shuffle()` method
     ///
-    /// Creates new deck, shuffles it, and prints both pre- and post-shuffle decks
-    ///
-    /// **NOTE:** must manually verify that the order has changed using `cargo test -- --nocapture`
+    /// Shuffles a new deck and asserts it still holds the same 52 cards
     #[test]
     fn test_deck_shuffle() {
         let mut d = Deck::new();
+        d.shuffle();
 
-        for i in 0..52 {
-            println!("Item {}: {:?}", i + 1, d.cards[i]);
-        }
-        println!();
+        let codes = |deck: &Deck| {
+            let mut codes: Vec<String> = deck.cards.iter().map(|card| card.code()).collect();
+            codes.sort();
+            codes
+        };
+        assert_eq!(d.cards.len(), 52);
+        assert_eq!(codes(&d), codes(&Deck::new()));
+    }
 
-        d.shuffle();
+    /// Test `shuffle_with()` method
+    ///
+    /// Shuffles two decks with identically seeded RNGs, asserts they end up in the same
+    /// order as each other and in a different order than a fresh deck
+    #[test]
+    fn test_seeded_shuffle() {
+        let mut d1 = Deck::new();
+        let mut d2 = Deck::new();
 
-        for i in 0..52 {
-            println!("Item {}: {:?}", i + 1, d.cards[i]);
-        }
+        d1.shuffle_with(&mut StdRng::seed_from_u64(42));
+        d2.shuffle_with(&mut StdRng::seed_from_u64(42));
+
+        assert_eq!(d1, d2);
+        assert_ne!(d1, Deck::new());
     }
 
     /// Test `deal()` method
@@ -98,25 +127,31 @@ mod tests {
         assert_eq!(card, return_card.unwrap())
     }
 
-    /// Test `deal()` method with deck replenishment
+    /// Test `from_cards()` constructor
     ///
-    /// Creates new deck, deals 33 cards to reduce deck size to 19,
-    /// then deals one more card to trigger deck replenishment,
-    /// and asserts that the deck size is reset to 51 cards.
+    /// Stacks a deck with three cards and asserts they are dealt in order
     #[test]
-    fn test_deal_with_replenishment() {
-        let mut d = Deck::new();
-        for _ in 0..32 {
-            d.deal();
-        }
-        assert_eq!(d.cards.len(), 20);
-        d.deal();
-        assert_eq!(d.cards.len(), 51);
+    fn test_stacked_deck() {
+        let cards = crate::card::parse_cards("AS KH 5D").unwrap();
+        let mut d = Deck::from_cards(cards.clone());
+
+        assert_eq!(d.deal(), Some(cards[0]));
+        assert_eq!(d.deal(), Some(cards[1]));
+        assert_eq!(d.deal(), Some(cards[2]));
+        assert_eq!(d.deal(), None);
+    }
 
-        let mut d2 = Deck::new();
-        for _ in 0..60 {
-            d2.deal();
+    /// Test `deal()` method until the deck runs out
+    ///
+    /// Deals all 52 cards, asserts the deck is empty and that dealing again returns `None`
+    /// instead of silently rebuilding the deck
+    #[test]
+    fn test_deal_until_empty() {
+        let mut d = Deck::new();
+        for _ in 0..52 {
+            assert!(d.deal().is_some());
         }
-        assert_ne!(d2.cards.len(), 0);
+        assert_eq!(d.cards.len(), 0);
+        assert_eq!(d.deal(), None);
     }
 }
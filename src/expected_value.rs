@@ -0,0 +1,343 @@
+//! # Expected Value
+//!
+//! This module computes the exact expected value of every action in the current situation:
+//! the player's hand, the dealer's up card, and the cards the player hasn't seen yet.
+//!
+//! Unlike the tables of `basic_strategy`, every card drawn — by the player or the dealer — is
+//! taken out of the shoe, so the numbers follow the composition of the shoe card by card.
+//! Split hands are valued without resplitting, each drawing from the shoe as it is at the split.
+//! The tables of `basic_strategy` are valued with the same helpers for the dealer's odds after
+//! a peek, split hands and surrender.
+
+use std::collections::HashMap;
+
+use crate::{
+    basic_strategy::ActionValues,
+    card::Card,
+    dealer_odds::{DealerOdds, VALUES, hard},
+    game::{Action, Game},
+    hand::Hand,
+    round::Phase,
+    rules::{SurrenderRule, TableRules},
+    shoe::Composition,
+};
+
+/// Exact expected value of every action for `hand` against `dealer_upcard`, as a fraction of the bet
+///
+/// `unseen` holds the cards the player hasn't seen, including the dealer's hole card. At tables
+/// where the dealer peeks, the dealer is taken not to hold a blackjack, since play has started.
+/// Double, split and surrender are valued whenever the rules allow them for a hand like this
+pub fn action_values(
+    hand: &Hand,
+    dealer_upcard: Card,
+    unseen: &Composition,
+    rules: &TableRules,
+) -> ActionValues {
+    let mut calculator = Calculator {
+        rules,
+        upcard: dealer_upcard.value(),
+        dealer: HashMap::new(),
+        best: HashMap::new(),
+    };
+
+    let cards = &hand.cards;
+    let points = cards.iter().map(|card| hard(card.value())).sum();
+    let ace = cards.iter().any(|card| card.value() == 11);
+    let starting = cards.len() == 2;
+    let pair = (starting && cards[0].value() == cards[1].value()).then(|| cards[0].value());
+
+    let blackjack = calculator.dealer(unseen).blackjack;
+
+    ActionValues {
+        stand: calculator.stand(unseen, points, ace),
+        hit: calculator.hit(unseen, points, ace),
+        double: (starting && rules.double.allows(hand.value()))
+            .then(|| calculator.double(unseen, points, ace)),
+        split: pair
+            .filter(|_| rules.max_split_hands >= 2)
+            .map(|pair| 2.0 * calculator.split_hand(unseen, pair)),
+        surrender: starting
+            .then(|| surrender_value(rules, blackjack))
+            .flatten(),
+    }
+}
+
+/// Expected value of surrendering, when the dealer holds a blackjack with chance `blackjack`
+///
+/// `None` if the rules don't allow surrender
+pub(crate) fn surrender_value(rules: &TableRules, blackjack: f64) -> Option<f64> {
+    match rules.surrender {
+        SurrenderRule::NotAllowed => None,
+        // Without a peek, a late surrender still loses everything to a dealer blackjack
+        SurrenderRule::Late => Some(-blackjack - 0.5 * (1.0 - blackjack)),
+        SurrenderRule::Early => Some(-0.5),
+    }
+}
+
+/// Expected value of a split hand totalling `total` once it is dealt its second card
+///
+/// `value` gives the expected value of `Stand`, of `Hit` and playing on as best, and of `Double`.
+/// Split aces stand unless the rules let them be hit, and a double only counts where the rules
+/// allow one after a split
+pub(crate) fn split_hand_value(
+    rules: &TableRules,
+    aces: bool,
+    total: u8,
+    mut value: impl FnMut(Action) -> f64,
+) -> f64 {
+    if aces && !rules.hit_split_aces {
+        value(Action::Stand)
+    } else if rules.double_after_split && rules.double.allows(total) {
+        value(Action::Hit).max(value(Action::Double))
+    } else {
+        value(Action::Hit)
+    }
+}
+
+/// Dealer odds under an up card worth `upcard`, drawing from `unseen`
+///
+/// At tables that peek, the player only plays on once the dealer has no blackjack, so the odds
+/// are taken given none
+pub(crate) fn dealer_odds(upcard: u8, unseen: &Composition, rules: &TableRules) -> DealerOdds {
+    let odds = DealerOdds::with_upcard_value(upcard, unseen, rules.dealer_hits_soft_17);
+
+    if rules.hole_card {
+        odds.given_no_blackjack()
+    } else {
+        odds
+    }
+}
+
+/// Works out expected values for one up card, remembering results by the cards left in the shoe
+struct Calculator<'a> {
+    rules: &'a TableRules,
+    upcard: u8,
+    /// Dealer odds for each shoe composition reached
+    dealer: HashMap<Composition, DealerOdds>,
+    /// Best of standing or hitting, by shoe composition and the hand's hard total and ace
+    best: HashMap<(Composition, u8, bool), f64>,
+}
+
+impl Calculator<'_> {
+    /// Dealer odds drawing from `unseen`, without a blackjack if the dealer has peeked
+    fn dealer(&mut self, unseen: &Composition) -> DealerOdds {
+        if let Some(odds) = self.dealer.get(unseen) {
+            return *odds;
+        }
+
+        let odds = dealer_odds(self.upcard, unseen, self.rules);
+        self.dealer.insert(*unseen, odds);
+        odds
+    }
+
+    /// Expected value of standing on a hand worth `points` (aces as 1)
+    fn stand(&mut self, unseen: &Composition, points: u8, ace: bool) -> f64 {
+        self.dealer(unseen).stand_ev(total(points, ace))
+    }
+
+    /// Expected value of hitting, then playing on by hitting or standing
+    fn hit(&mut self, unseen: &Composition, points: u8, ace: bool) -> f64 {
+        self.draw(unseen, points, ace, Calculator::best)
+    }
+
+    /// Expected value of doubling: twice the bet, exactly one more card
+    fn double(&mut self, unseen: &Composition, points: u8, ace: bool) -> f64 {
+        2.0 * self.draw(unseen, points, ace, Calculator::stand)
+    }
+
+    /// Best of standing or hitting
+    fn best(&mut self, unseen: &Composition, points: u8, ace: bool) -> f64 {
+        if let Some(&best) = self.best.get(&(*unseen, points, ace)) {
+            return best;
+        }
+
+        let best = self
+            .stand(unseen, points, ace)
+            .max(self.hit(unseen, points, ace));
+
+        self.best.insert((*unseen, points, ace), best);
+        best
+    }
+
+    /// Expected value of one hand of a split pair of cards worth `pair`, dealt its second card
+    fn split_hand(&mut self, unseen: &Composition, pair: u8) -> f64 {
+        let aces = pair == 11;
+
+        self.draw(
+            unseen,
+            hard(pair),
+            aces,
+            |calculator, unseen, points, ace| {
+                let rules = calculator.rules;
+
+                split_hand_value(rules, aces, total(points, ace), |action| match action {
+                    Action::Stand => calculator.stand(unseen, points, ace),
+                    Action::Double => calculator.double(unseen, points, ace),
+                    _ => calculator.best(unseen, points, ace),
+                })
+            },
+        )
+    }
+
+    /// Expected value of drawing one card from `unseen`, valuing each resulting hand with `then`
+    fn draw(
+        &mut self,
+        unseen: &Composition,
+        points: u8,
+        ace: bool,
+        then: impl Fn(&mut Self, &Composition, u8, bool) -> f64,
+    ) -> f64 {
+        let cards = unseen.total() as f64;
+        let mut ev = 0.0;
+
+        for value in VALUES {
+            let count = unseen.count(value);
+            if count == 0 {
+                continue;
+            }
+
+            let p = count as f64 / cards;
+            let drawn = points + hard(value);
+            if drawn > 21 {
+                ev -= p;
+                continue;
+            }
+
+            let mut rest = *unseen;
+            rest.remove_value(value);
+            ev += p * then(self, &rest, drawn, ace || value == 11);
+        }
+
+        ev
+    }
+}
+
+/// Best total of a hand worth `points` with aces as 1, counting an ace as 11 if it holds one and it fits
+pub(crate) fn total(points: u8, ace: bool) -> u8 {
+    if ace && points + 10 <= 21 {
+        points + 10
+    } else {
+        points
+    }
+}
+
+impl Game {
+    /// Exact expected value of each action for the active hand, from the cards the player hasn't seen
+    ///
+    /// Actions the player can't take right now are `None`. Returns `None` outside the player's turn,
+    /// when there is no hand to play
+    pub fn action_values(&self) -> Option<ActionValues> {
+        if self.phase() != Phase::PlayerTurn {
+            return None;
+        }
+
+        let mut values = action_values(
+            self.player_hand(),
+            self.dealer_hand.cards[0],
+            &self.unseen_cards(),
+            &self.rules,
+        );
+        let allowed = self.allowed_actions();
+
+        if !allowed.contains(&Action::Double) {
+            values.double = None;
+        }
+        if !allowed.contains(&Action::Split) {
+            values.split = None;
+        }
+        if !allowed.contains(&Action::Surrender) {
+            values.surrender = None;
+        }
+
+        Some(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::card::{parse_cards, parse_hand};
+
+    /// A fresh shoe of `decks` decks with `seen` taken out
+    fn unseen(decks: u8, seen: &str) -> Composition {
+        let mut unseen = Composition::full(decks);
+        for card in parse_cards(seen).unwrap() {
+            unseen.remove(&card);
+        }
+        unseen
+    }
+
+    /// Test well-known expected values
+    ///
+    /// From a fresh 6-deck shoe, asserts 16 vs 10 loses about 54% standing,
+    /// and 11 vs 6 is worth about two thirds of the bet doubled
+    #[test]
+    fn test_fresh_shoe_values() {
+        let rules = TableRules {
+            decks: 6,
+            ..TableRules::vegas_strip()
+        };
+
+        let values = action_values(
+            &parse_hand("TS 6H").unwrap(),
+            "TC".parse().unwrap(),
+            &unseen(6, "TS 6H TC"),
+            &rules,
+        );
+        assert!((values.stand + 0.54).abs() < 0.01);
+        assert!(values.hit > values.stand);
+        assert_eq!(values.split, None);
+
+        let values = action_values(
+            &parse_hand("6S 5H").unwrap(),
+            "6C".parse().unwrap(),
+            &unseen(6, "6S 5H 6C"),
+            &rules,
+        );
+        assert!((values.double.unwrap() - 0.67).abs() < 0.02);
+        assert_eq!(values.best(&[Action::Hit, Action::Stand]), Action::Hit);
+    }
+
+    /// Test that values follow the composition
+    ///
+    /// With only tens left, 12 vs 4 busts on every hit, while the dealer's 14 always busts:
+    /// standing wins the whole bet and hitting loses it
+    #[test]
+    fn test_composition_dependence() {
+        let tens = Composition::from_cards(&parse_cards("TS TH TD TC KS KH QD JC").unwrap());
+        let values = action_values(
+            &parse_hand("TS 2H").unwrap(),
+            "4C".parse().unwrap(),
+            &tens,
+            &TableRules::default(),
+        );
+
+        assert_eq!(values.stand, 1.0);
+        assert_eq!(values.hit, -1.0);
+        assert_eq!(values.double, Some(-2.0));
+    }
+
+    /// Test `Game::action_values()`
+    ///
+    /// Asserts nothing is valued before the deal. After a hit, asserts only standing and hitting
+    /// are valued, from the three nines left in the shoe and the hidden seven: standing on 10
+    /// always loses, hitting does better
+    #[test]
+    fn test_game_action_values() {
+        let mut game = Game::scripted(TableRules::default(), "5H TS 3C 7D 2H 9S 9C 9D");
+        assert_eq!(game.action_values(), None);
+
+        game.apply(Action::Bet(10)).unwrap();
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+        assert!(game.action_values().unwrap().double.is_some());
+
+        game.apply(Action::Hit).unwrap();
+        let values = game.action_values().unwrap();
+
+        assert_eq!(values.double, None);
+        assert_eq!(values.split, None);
+        assert_eq!(values.surrender, None);
+        assert_eq!(values.stand, -1.0);
+        assert!(values.hit > values.stand);
+    }
+}
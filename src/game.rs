@@ -5,71 +5,294 @@
 //!
 //! It is meant to separate the actual blackjack rules from the main executable.
 
-use crate::{deck::Deck, hand::*};
+use crate::{
+    card::{Card, Rank},
+    hand::*,
+    round::{GameError, Offer, Phase},
+    rules::{SurrenderRule, TableRules},
+    shoe::{Composition, Shoe},
+};
 
 /// Contains all possible round results
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RoundResult {
     PlayerWin,
     PlayerBlackjack,
     DealerWin,
     Push,
+    Surrender,
+}
+
+/// Contains all decisions a player can make, passed to `Game::apply()`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Action {
+    /// Place a bet and deal the next round
+    Bet(u32),
+    /// Accept the insurance offer for the given amount
+    Insurance(u32),
+    /// Accept the even money offer
+    EvenMoney,
+    /// Turn down the current insurance, even money or early surrender offer
+    Decline,
+    Hit,
+    Stand,
+    Double,
+    Split,
+    Surrender,
+}
+
+/// One of the player's hands, with the bet riding on it
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerHand {
+    pub hand: Hand,
+    pub bet: u32,
+    /// True if this hand was created by splitting a pair
+    pub is_split: bool,
+    pub is_doubled: bool,
+    pub is_surrendered: bool,
+}
+
+impl PlayerHand {
+    /// Constructor: Creates an empty hand carrying the given bet
+    pub fn new(bet: u32) -> Self {
+        PlayerHand {
+            hand: Hand::new(),
+            bet,
+            is_split: false,
+            is_doubled: false,
+            is_surrendered: false,
+        }
+    }
+
+    /// Check if the hand is a natural blackjack (split hands totaling 21 don't count)
+    pub fn is_natural(&self) -> bool {
+        !self.is_split && self.hand.is_blackjack()
+    }
+
+    /// Check if the hand is a pair of split aces
+    fn is_split_aces(&self) -> bool {
+        self.is_split && self.hand.cards.first().map(|c| *c.rank()) == Some(Rank::ACE)
+    }
 }
 
 /// The Game struct contains all data that needs to be
 /// managed and tracked throughout a blackjack game session
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Game {
-    deck: Deck,
-    pub player_hand: Hand,
+    shoe: Shoe,
+    /// Every hand the player holds this round; more than one after splitting
+    pub player_hands: Vec<PlayerHand>,
+    /// Index into `player_hands` of the hand currently being played
+    pub active_hand: usize,
     pub dealer_hand: Hand,
+    /// False while the dealer's hole card is still face down
+    pub hole_revealed: bool,
     pub bankroll: u32,
+    /// The bet placed at the start of the round
     pub bet: u32,
+    /// Insurance side bet placed when the dealer shows an ace, paying 2:1
+    pub insurance: u32,
+    /// True if the player took even money on a blackjack against a dealer ace
+    pub even_money: bool,
     pub i: u16,
+    pub rules: TableRules,
+    pub(crate) phase: Phase,
+    /// Offers still waiting for a decision during `Phase::Dealing`
+    pub(crate) offers: Vec<Offer>,
+    /// True if the shoe ran dry and was reshuffled since the round engine last reported it
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) reshuffled: bool,
 }
 
 impl Game {
-    /// Constructor: Creates new game session with starting bankroll and initializes deck and hands
-    pub fn new(starting_bankroll: u32) -> Self {
-        let mut deck = Deck::new();
-        deck.shuffle();
+    /// Constructor: Creates new game session with starting bankroll and table rules, and initializes deck and hands
+    ///
+    /// The shoe is shuffled from a random seed; see `with_seed()` for reproducible games
+    pub fn new(starting_bankroll: u32, rules: TableRules) -> Self {
+        Game::with_seed(starting_bankroll, rules, rand::random())
+    }
 
-        let player_hand = Hand::new();
+    /// Constructor: Like `new()`, but every shuffle of the shoe is drawn from `seed`,
+    /// so the same seed and the same decisions replay the exact same session
+    pub fn with_seed(starting_bankroll: u32, rules: TableRules, seed: u64) -> Self {
+        let shoe = Shoe::with_seed(rules.decks, rules.penetration, seed);
+
+        Game::with_shoe(starting_bankroll, rules, shoe)
+    }
+
+    /// Constructor: Creates a game dealing from the given shoe, e.g. a `Shoe::stacked()`
+    /// one to script whole rounds
+    pub fn with_shoe(starting_bankroll: u32, rules: TableRules, shoe: Shoe) -> Self {
+        let player_hands = vec![PlayerHand::new(0)];
         let dealer_hand = Hand::new();
 
         Game {
-            deck,
-            player_hand,
+            shoe,
+            player_hands,
+            active_hand: 0,
             dealer_hand,
+            hole_revealed: false,
             bankroll: starting_bankroll,
             bet: 0,
+            insurance: 0,
+            even_money: false,
             i: 0,
+            rules,
+            phase: Phase::Betting,
+            offers: Vec::new(),
+            reshuffled: false,
         }
     }
 
-    /// Initial deal that deals two cards to player and one to dealer to represent dealer's face-up card
-    pub fn initial_deal(&mut self) {
-        self.player_hand.clear();
+    /// Test constructor: A game with $1000 at `rules`, dealing the given card codes in order
+    #[cfg(test)]
+    pub(crate) fn scripted(rules: TableRules, cards: &str) -> Self {
+        let shoe = Shoe::stacked(crate::card::parse_cards(cards).unwrap());
+        Game::with_shoe(1000, rules, shoe)
+    }
+
+    /// Initial deal that deals two cards to player, and to the dealer a face-up card
+    /// plus a face-down hole card (no hole card at a European no-hole-card table)
+    ///
+    /// Starts the round with a single player hand carrying `bet`. If the cut card came out
+    /// last round, the shoe is shuffled first and `true` is returned so the UI can say so.
+    /// Fails with `GameError::OutOfCards` if a stacked shoe runs out
+    pub fn initial_deal(&mut self) -> Result<bool, GameError> {
+        let shuffled = self.shoe.needs_shuffle();
+        if shuffled {
+            self.shoe.shuffle();
+        }
+
+        self.player_hands = vec![PlayerHand::new(self.bet)];
+        self.active_hand = 0;
+        self.insurance = 0;
+        self.even_money = false;
         self.dealer_hand.clear();
+        self.hole_revealed = false;
+
+        self.deal_to_player()?;
+        self.deal_to_dealer()?;
+        self.deal_to_player()?;
+        if self.rules.hole_card {
+            self.deal_to_dealer()?;
+        }
+
+        Ok(shuffled || std::mem::take(&mut self.reshuffled))
+    }
+
+    /// Returns the shoe being dealt from
+    pub fn shoe(&self) -> &Shoe {
+        &self.shoe
+    }
+
+    /// Cards the player hasn't seen: those left in the shoe plus the dealer's hidden hole card
+    pub fn unseen_cards(&self) -> Composition {
+        let mut unseen = self.shoe.composition();
+        if self.hole_card_hidden() {
+            unseen.add(&self.dealer_hand.cards[1]);
+        }
+        unseen
+    }
+
+    /// The seed the shoe is shuffled from, for replaying this session
+    pub fn seed(&self) -> u64 {
+        self.shoe.seed()
+    }
+
+    /// Check if the dealer's hole card is dealt but still face down
+    pub fn hole_card_hidden(&self) -> bool {
+        !self.hole_revealed && self.dealer_hand.cards.len() > 1
+    }
 
-        self.player_hand.add_card(self.deck.deal().unwrap());
-        self.dealer_hand.add_card(self.deck.deal().unwrap());
-        self.player_hand.add_card(self.deck.deal().unwrap());
+    /// Turn the dealer's hole card face up
+    ///
+    /// At a no-hole-card table the dealer's second card is dealt now instead
+    pub fn reveal_hole_card(&mut self) -> Result<(), GameError> {
+        if self.dealer_hand.cards.len() < 2 {
+            self.deal_to_dealer()?;
+        }
+        self.hole_revealed = true;
+        Ok(())
     }
 
-    /// Deal a card to the player
-    pub fn deal_to_player(&mut self) {
-        self.player_hand.add_card(self.deck.deal().unwrap());
+    /// Check if the dealer peeks for blackjack: a hole card under a ten-valued card or ace
+    pub fn dealer_peeks(&self) -> bool {
+        self.rules.hole_card
+            && self.dealer_hand.cards.len() == 2
+            && self.dealer_hand.cards[0].value() >= 10
+    }
+
+    /// American peek: the dealer checks the hole card for blackjack before the player acts
+    ///
+    /// Returns true (and reveals the hole card) if the dealer has blackjack and the round is over
+    pub fn peek_for_blackjack(&mut self) -> bool {
+        if self.dealer_peeks() && self.dealer_hand.is_blackjack() {
+            self.hole_revealed = true;
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns the hand currently being played
+    pub fn player_hand(&self) -> &Hand {
+        &self.player_hands[self.active_hand].hand
+    }
+
+    /// Returns the hand currently being played, mutably
+    pub fn player_hand_mut(&mut self) -> &mut Hand {
+        &mut self.player_hands[self.active_hand].hand
+    }
+
+    /// Deal a card to the player's active hand
+    pub fn deal_to_player(&mut self) -> Result<(), GameError> {
+        let card = self.draw()?;
+        self.player_hand_mut().add_card(card);
+        Ok(())
     }
 
     /// Deal a card to the dealer
-    pub fn deal_to_dealer(&mut self) {
-        self.dealer_hand.add_card(self.deck.deal().unwrap());
+    pub fn deal_to_dealer(&mut self) -> Result<(), GameError> {
+        let card = self.draw()?;
+        self.dealer_hand.add_card(card);
+        Ok(())
+    }
+
+    /// Take the next card from the shoe
+    ///
+    /// If the shoe runs dry mid-round, every card not on the table is shuffled back in.
+    /// A stacked shoe is never refilled, so it fails with `GameError::OutOfCards`
+    fn draw(&mut self) -> Result<Card, GameError> {
+        if let Some(card) = self.shoe.deal() {
+            return Ok(card);
+        }
+
+        let in_play: Vec<Card> = self
+            .player_hands
+            .iter()
+            .flat_map(|player| player.hand.cards.iter())
+            .chain(self.dealer_hand.cards.iter())
+            .copied()
+            .collect();
+        self.shoe.shuffle_without(&in_play);
+
+        let card = self.shoe.deal().ok_or(GameError::OutOfCards)?;
+        self.reshuffled = true;
+        Ok(card)
     }
 
-    /// Check if player has busted
+    /// Check if player's active hand has busted
     pub fn player_bust(&self) -> bool {
-        self.player_hand.value() > 21
+        self.player_hand().value() > 21
+    }
+
+    /// Check if every one of the player's hands has busted
+    pub fn all_hands_bust(&self) -> bool {
+        self.player_hands.iter().all(|h| h.hand.value() > 21)
     }
 
     /// Check if dealer has busted
@@ -77,41 +300,288 @@ impl Game {
         self.dealer_hand.value() > 21
     }
 
+    /// Total amount bet across all of the player's hands
+    pub fn total_bet(&self) -> u32 {
+        self.player_hands.iter().map(|h| h.bet).sum()
+    }
+
+    /// Check if the bankroll covers another bet the size of the active hand's
+    fn can_cover_active_bet(&self) -> bool {
+        self.total_bet() + self.insurance + self.player_hands[self.active_hand].bet <= self.bankroll
+    }
+
+    /// Check if the dealer's face-up card is an ace, so insurance or even money should be offered
+    pub fn insurance_offered(&self) -> bool {
+        self.dealer_hand.cards.first().map(|c| *c.rank()) == Some(Rank::ACE)
+    }
+
+    /// Largest insurance bet allowed: half the original bet, limited by the bankroll left over
+    pub fn max_insurance(&self) -> u32 {
+        (self.bet / 2).min(self.bankroll.saturating_sub(self.total_bet()))
+    }
+
+    /// Place an insurance side bet, capped at `max_insurance()`
+    ///
+    /// Does nothing if the dealer isn't showing an ace
+    pub fn take_insurance(&mut self, amount: u32) {
+        if self.insurance_offered() {
+            self.insurance = amount.min(self.max_insurance());
+        }
+    }
+
+    /// Take even money on a blackjack against a dealer ace: the hand is paid 1:1
+    /// right away, whatever the dealer holds
+    ///
+    /// Does nothing unless the player holds a natural and the dealer shows an ace
+    pub fn take_even_money(&mut self) {
+        if self.insurance_offered() && self.player_hands[0].is_natural() {
+            self.even_money = true;
+        }
+    }
+
+    /// Check if the insurance side bet won, i.e. the dealer has blackjack
+    pub fn insurance_won(&self) -> bool {
+        self.dealer_hand.is_blackjack()
+    }
+
+    /// Check if the player may hit the active hand
+    ///
+    /// Split aces may only be hit if the table allows it, even while they can still be resplit
+    pub fn can_hit(&self) -> bool {
+        let player_hand = &self.player_hands[self.active_hand];
+
+        !self.hand_finished() && (!player_hand.is_split_aces() || self.rules.hit_split_aces)
+    }
+
+    /// Check if the player may double down
+    ///
+    /// Requires a two-card hand whose total the table allows doubling on,
+    /// and enough bankroll to cover the doubled bet
+    pub fn can_double(&self) -> bool {
+        let player_hand = &self.player_hands[self.active_hand];
+
+        if player_hand.hand.cards.len() != 2 || !self.can_cover_active_bet() {
+            return false;
+        }
+        if player_hand.is_split && !self.rules.double_after_split {
+            return false;
+        }
+        if player_hand.is_split_aces() && !self.rules.hit_split_aces {
+            return false;
+        }
+
+        self.rules.double.allows(player_hand.hand.value())
+    }
+
+    /// Double down: doubles the active hand's bet and deals exactly one more card to it
+    ///
+    /// The hand is finished afterwards. Does nothing if `can_double()` is false.
+    pub fn double_down(&mut self) -> Result<(), GameError> {
+        if !self.can_double() {
+            return Ok(());
+        }
+
+        let player_hand = &mut self.player_hands[self.active_hand];
+        player_hand.bet *= 2;
+        player_hand.is_doubled = true;
+
+        self.deal_to_player()
+    }
+
+    /// Check if the player may surrender
+    ///
+    /// Only allowed as the first decision of the round, on the original two-card hand
+    pub fn can_surrender(&self) -> bool {
+        self.rules.surrender != SurrenderRule::NotAllowed
+            && self.player_hands.len() == 1
+            && self.player_hand().cards.len() == 2
+            && !self.player_hands[0].is_surrendered
+    }
+
+    /// Surrender the hand, giving up half the bet. Does nothing if `can_surrender()` is false.
+    ///
+    /// Under late surrender the dealer checks for blackjack first, so a surrender
+    /// against a dealer blackjack still loses the whole bet
+    pub fn surrender(&mut self) {
+        if self.can_surrender() {
+            self.player_hands[0].is_surrendered = true;
+        }
+    }
+
+    /// Amount refunded when surrendering the given bet, per the table's odd-chip rounding
+    pub fn surrender_refund(&self, bet: u32) -> u32 {
+        self.rules.surrender_rounding.refund(bet)
+    }
+
+    /// Check if the player may split their active hand
+    ///
+    /// Requires a pair of equal-valued cards, room under the table's split limit,
+    /// enough bankroll for the second bet, and permission to resplit aces if needed
+    pub fn can_split(&self) -> bool {
+        let player_hand = &self.player_hands[self.active_hand];
+        let cards = &player_hand.hand.cards;
+
+        if cards.len() != 2 || cards[0].value() != cards[1].value() {
+            return false;
+        }
+        if self.player_hands.len() >= self.rules.max_split_hands as usize {
+            return false;
+        }
+        if player_hand.is_split_aces() && !self.rules.resplit_aces {
+            return false;
+        }
+
+        self.can_cover_active_bet()
+    }
+
+    /// Split the active pair into two hands, each carrying the original bet
+    ///
+    /// The second card moves to a new hand right after the active one, which is dealt
+    /// its second card when it comes into play. The active hand is dealt a new card now.
+    /// Does nothing if `can_split()` is false.
+    pub fn split(&mut self) -> Result<(), GameError> {
+        if !self.can_split() {
+            return Ok(());
+        }
+
+        let player_hand = &mut self.player_hands[self.active_hand];
+        let card = player_hand.hand.cards.pop().unwrap();
+        player_hand.is_split = true;
+
+        let mut new_hand = PlayerHand::new(player_hand.bet);
+        new_hand.is_split = true;
+        new_hand.hand.add_card(card);
+
+        self.player_hands.insert(self.active_hand + 1, new_hand);
+        self.deal_to_player()
+    }
+
+    /// Check if the active hand can take no further action
+    ///
+    /// A hand is finished once it busts, after doubling down, or when it is a
+    /// pair of split aces that may not be hit (and can't be resplit)
+    pub fn hand_finished(&self) -> bool {
+        let player_hand = &self.player_hands[self.active_hand];
+
+        if self.player_bust() || player_hand.is_doubled || player_hand.is_surrendered {
+            return true;
+        }
+
+        player_hand.is_split_aces() && !self.rules.hit_split_aces && !self.can_split()
+    }
+
+    /// Move on to the player's next hand, dealing its second card if it came from a split
+    ///
+    /// Returns false if there are no hands left to play
+    pub fn next_hand(&mut self) -> Result<bool, GameError> {
+        if self.active_hand + 1 >= self.player_hands.len() {
+            return Ok(false);
+        }
+
+        self.active_hand += 1;
+        if self.player_hand().cards.len() < 2 {
+            self.deal_to_player()?;
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the actions available for the active hand during the player's turn
+    pub fn allowed_actions(&self) -> Vec<Action> {
+        let mut actions = vec![Action::Stand];
+
+        if self.can_hit() {
+            actions.insert(0, Action::Hit);
+        }
+        if self.can_double() {
+            actions.push(Action::Double);
+        }
+        if self.can_split() {
+            actions.push(Action::Split);
+        }
+        if self.can_surrender() {
+            actions.push(Action::Surrender);
+        }
+
+        actions
+    }
+
+    /// Check if any hand is still live against the dealer (not busted or surrendered)
+    pub fn dealer_must_play(&self) -> bool {
+        self.player_hands
+            .iter()
+            .any(|h| h.hand.value() <= 21 && !h.is_surrendered)
+    }
+
+    /// Check if the dealer must draw another card
+    ///
+    /// Dealer draws below 17, and on soft 17 only if the table rules say so
+    pub fn dealer_should_hit(&self) -> bool {
+        let value = self.dealer_hand.value();
+
+        value < 17 || (value == 17 && self.dealer_hand.is_soft() && self.rules.dealer_hits_soft_17)
+    }
+
+    /// Winnings for a player blackjack on the current bet, per the table's payout ratio
+    pub fn blackjack_payout(&self) -> u32 {
+        self.rules.blackjack_payout.pay(self.bet)
+    }
+
     /// Win Decision Logic
     ///
-    /// Determines the winner of the round based on blackjack rules
+    /// Determines the result of each of the player's hands against the dealer
+    ///
+    /// Returns one `RoundResult` per hand, in the same order as `player_hands`
+    pub fn determine_winner(&self) -> Vec<RoundResult> {
+        self.player_hands
+            .iter()
+            .map(|h| self.hand_result(h))
+            .collect()
+    }
+
+    /// Determines the result of a single player hand based on blackjack rules
     ///
     /// Follows correct priority:
-    /// 1. Busts
-    /// 2. Blackjacks
-    /// 3. Hand Value Comparison
-    ///
-    /// Returns `RoundResult` enum indicating outcome
-    pub fn determine_winner(&self) -> RoundResult {
-        if self.player_bust() {
-            return RoundResult::DealerWin;
+    /// 1. Even money / surrender
+    /// 2. Busts
+    /// 3. Blackjacks
+    /// 4. Hand Value Comparison
+    fn hand_result(&self, player_hand: &PlayerHand) -> RoundResult {
+        let hand = &player_hand.hand;
+
+        if self.even_money {
+            RoundResult::PlayerWin
+        } else if player_hand.is_surrendered {
+            if self.rules.surrender == SurrenderRule::Late && self.dealer_hand.is_blackjack() {
+                RoundResult::DealerWin
+            } else {
+                RoundResult::Surrender
+            }
+        } else if hand.value() > 21 {
+            RoundResult::DealerWin
         } else if self.dealer_bust() {
-            return RoundResult::PlayerWin;
-        } else if self.player_hand.is_blackjack() && self.dealer_hand.is_blackjack() {
-            return RoundResult::Push;
-        } else if self.player_hand.is_blackjack() {
-            return RoundResult::PlayerBlackjack;
+            RoundResult::PlayerWin
+        } else if player_hand.is_natural() && self.dealer_hand.is_blackjack() {
+            RoundResult::Push
+        } else if player_hand.is_natural() {
+            RoundResult::PlayerBlackjack
         } else if self.dealer_hand.is_blackjack() {
-            return RoundResult::DealerWin;
-        } else if self.player_hand.value() > self.dealer_hand.value() {
-            return RoundResult::PlayerWin;
-        } else if self.player_hand.value() < self.dealer_hand.value() {
-            return RoundResult::DealerWin;
+            RoundResult::DealerWin
+        } else if hand.value() > self.dealer_hand.value() {
+            RoundResult::PlayerWin
+        } else if hand.value() < self.dealer_hand.value() {
+            RoundResult::DealerWin
         } else {
-            return RoundResult::Push;
+            RoundResult::Push
         }
     }
 }
 
 #[cfg(test)]
-
 mod test {
     use crate::card::*;
+    use crate::round::GameError;
+    use crate::rules::DoubleRule;
 
     use super::*;
 
@@ -120,11 +590,11 @@ mod test {
     /// Gives player a blackjack and dealer a non-blackjack 21, asserts player win
     #[test]
     fn test_player_blackjack_win() {
-        let mut game = Game::new(1000);
+        let mut game = Game::new(1000, TableRules::default());
 
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::ACE));
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::TEN));
 
         game.dealer_hand
@@ -134,7 +604,7 @@ mod test {
         game.dealer_hand
             .add_card(Card::new(Suit::DIAMONDS, Rank::ACE));
 
-        assert_eq!(game.determine_winner(), RoundResult::PlayerBlackjack);
+        assert_eq!(game.determine_winner(), vec![RoundResult::PlayerBlackjack]);
     }
 
     /// Test dealer blackjack win scenario
@@ -142,13 +612,13 @@ mod test {
     /// Gives dealer a blackjack and player a non-blackjack 21, asserts dealer win
     #[test]
     fn test_dealer_blackjack_win() {
-        let mut game = Game::new(1000);
+        let mut game = Game::new(1000, TableRules::default());
 
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::TEN));
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::TEN));
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::ACE));
 
         game.dealer_hand
@@ -156,7 +626,7 @@ mod test {
         game.dealer_hand
             .add_card(Card::new(Suit::DIAMONDS, Rank::TEN));
 
-        assert_eq!(game.determine_winner(), RoundResult::DealerWin);
+        assert_eq!(game.determine_winner(), vec![RoundResult::DealerWin]);
     }
 
     /// Test push scenario
@@ -164,12 +634,12 @@ mod test {
     /// Gives both player and dealer hands of equal value, asserts push result
     #[test]
     fn test_push() {
-        let mut game = Game::new(1000);
+        let mut game = Game::new(1000, TableRules::default());
 
         // Both have 20
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::TEN));
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::TEN));
 
         game.dealer_hand
@@ -178,7 +648,7 @@ mod test {
             .add_card(Card::new(Suit::DIAMONDS, Rank::TEN));
 
         let result = game.determine_winner();
-        assert!(matches!(result, RoundResult::Push));
+        assert!(matches!(result[..], [RoundResult::Push]));
     }
 
     /// Test player bust scenario
@@ -186,13 +656,13 @@ mod test {
     /// Gives player a busting hand and dealer a valid hand, asserts dealer win
     #[test]
     fn test_player_bust() {
-        let mut game = Game::new(1000);
+        let mut game = Game::new(1000, TableRules::default());
 
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::TEN));
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::KING));
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::TWO));
 
         game.dealer_hand
@@ -200,7 +670,7 @@ mod test {
         game.dealer_hand
             .add_card(Card::new(Suit::DIAMONDS, Rank::SEVEN));
 
-        assert_eq!(game.determine_winner(), RoundResult::DealerWin);
+        assert_eq!(game.determine_winner(), vec![RoundResult::DealerWin]);
     }
 
     /// Test dealer bust scenario
@@ -208,11 +678,11 @@ mod test {
     /// Gives dealer a busting hand and player a valid hand, asserts player win
     #[test]
     fn test_dealer_bust() {
-        let mut game = Game::new(1000);
+        let mut game = Game::new(1000, TableRules::default());
 
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::NINE));
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::SEVEN));
 
         game.dealer_hand
@@ -222,7 +692,7 @@ mod test {
         game.dealer_hand
             .add_card(Card::new(Suit::DIAMONDS, Rank::TWO));
 
-        assert_eq!(game.determine_winner(), RoundResult::PlayerWin);
+        assert_eq!(game.determine_winner(), vec![RoundResult::PlayerWin]);
     }
 
     /// Test blackjack push scenario
@@ -230,12 +700,12 @@ mod test {
     /// Gives both player and dealer blackjacks, asserts push result
     #[test]
     fn test_backjack_push() {
-        let mut game = Game::new(1000);
+        let mut game = Game::new(1000, TableRules::default());
 
         // Both have blackjack
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::ACE));
-        game.player_hand
+        game.player_hand_mut()
             .add_card(Card::new(Suit::DIAMONDS, Rank::KING));
 
         game.dealer_hand
@@ -244,6 +714,488 @@ mod test {
             .add_card(Card::new(Suit::DIAMONDS, Rank::QUEEN));
 
         let result = game.determine_winner();
-        assert!(matches!(result, RoundResult::Push));
+        assert!(matches!(result[..], [RoundResult::Push]));
+    }
+
+    /// Test dealer soft 17 rule
+    ///
+    /// Gives dealer a soft 17 and asserts they stand under S17 and hit under H17
+    #[test]
+    fn test_dealer_soft_17() {
+        let mut game = Game::new(1000, TableRules::default());
+
+        game.dealer_hand.add_card(Card::new(Suit::CLUBS, Rank::ACE));
+        game.dealer_hand.add_card(Card::new(Suit::CLUBS, Rank::SIX));
+
+        assert!(!game.dealer_should_hit());
+
+        game.rules.dealer_hits_soft_17 = true;
+        assert!(game.dealer_should_hit());
+    }
+
+    /// Test double down
+    ///
+    /// Doubles on 11, asserts the bet doubles and exactly one card is added
+    #[test]
+    fn test_double_down() {
+        let mut game = Game::new(1000, TableRules::default());
+        game.player_hands[0].bet = 100;
+
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::FIVE));
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::SIX));
+
+        assert!(game.allowed_actions().contains(&Action::Double));
+
+        game.double_down().unwrap();
+
+        assert_eq!(game.player_hands[0].bet, 200);
+        assert_eq!(game.player_hand().cards.len(), 3);
+        assert!(!game.can_double());
+    }
+
+    /// Test double down restrictions
+    ///
+    /// Asserts the 9-11 rule rejects a soft 18 and that the bankroll must cover the doubled bet
+    #[test]
+    fn test_double_restrictions() {
+        let mut game = Game::new(1000, TableRules::european());
+        game.player_hands[0].bet = 100;
+
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::ACE));
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::SEVEN));
+
+        assert!(!game.can_double());
+
+        game.rules.double = DoubleRule::AnyTwoCards;
+        assert!(game.can_double());
+
+        game.player_hands[0].bet = 600;
+        assert!(!game.can_double());
+    }
+
+    /// Test splitting a pair
+    ///
+    /// Splits eights, asserts two hands with equal bets exist, the first is dealt a new card
+    /// and the second receives its card once it comes into play
+    #[test]
+    fn test_split() {
+        let mut game = Game::new(1000, TableRules::default());
+        game.player_hands[0].bet = 100;
+
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::EIGHT));
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::SPADES, Rank::EIGHT));
+
+        assert!(game.allowed_actions().contains(&Action::Split));
+
+        game.split().unwrap();
+
+        assert_eq!(game.player_hands.len(), 2);
+        assert_eq!(game.total_bet(), 200);
+        assert_eq!(game.player_hand().cards.len(), 2);
+        assert_eq!(game.player_hands[1].hand.cards.len(), 1);
+
+        assert!(game.next_hand().unwrap());
+        assert_eq!(game.player_hand().cards.len(), 2);
+        assert!(!game.next_hand().unwrap());
+    }
+
+    /// Test split limits
+    ///
+    /// Asserts splitting stops at the table's hand limit and when the bankroll can't cover another bet
+    #[test]
+    fn test_split_limits() {
+        let mut game = Game::new(1000, TableRules::default());
+        game.rules.max_split_hands = 2;
+        game.player_hands[0].bet = 100;
+
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::KING));
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::SPADES, Rank::TEN));
+        game.player_hands.push(PlayerHand::new(100));
+
+        assert!(!game.can_split());
+
+        game.player_hands.pop();
+        game.player_hands[0].bet = 600;
+        assert!(!game.can_split());
+
+        game.player_hands[0].bet = 500;
+        assert!(game.can_split());
+    }
+
+    /// Test split aces
+    ///
+    /// Splits aces, asserts each hand is finished after one card and can't be doubled,
+    /// and that a split hand totaling 21 is not paid as a blackjack
+    #[test]
+    fn test_split_aces() {
+        let mut game = Game::new(1000, TableRules::default());
+
+        game.player_hands = vec![PlayerHand::new(100)];
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::ACE));
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::SPADES, Rank::ACE));
+        game.split().unwrap();
+
+        game.player_hands[0].hand.cards[1] = Card::new(Suit::CLUBS, Rank::KING);
+        assert!(game.hand_finished());
+        assert!(!game.can_double());
+        assert_eq!(game.allowed_actions(), vec![Action::Stand]);
+
+        game.dealer_hand
+            .add_card(Card::new(Suit::DIAMONDS, Rank::TEN));
+        game.dealer_hand
+            .add_card(Card::new(Suit::DIAMONDS, Rank::NINE));
+
+        assert_eq!(game.determine_winner()[0], RoundResult::PlayerWin);
+    }
+
+    /// Test resplitting aces that may not be hit
+    ///
+    /// Splits aces and draws another ace with resplitting allowed but hitting not, and asserts
+    /// the split hand can stand or resplit but a hit is rejected
+    #[test]
+    fn test_resplit_aces_without_hit() {
+        let rules = TableRules {
+            resplit_aces: true,
+            hit_split_aces: false,
+            ..TableRules::default()
+        };
+        let mut game = Game::scripted(rules, "AH 9C AD 7S AC");
+
+        game.apply(Action::Bet(10)).unwrap();
+        game.apply(Action::Split).unwrap();
+
+        assert_eq!(game.player_hand().cards.len(), 2);
+        assert!(!game.can_hit());
+        assert_eq!(game.allowed_actions(), vec![Action::Stand, Action::Split]);
+        assert_eq!(
+            game.apply(Action::Hit),
+            Err(GameError::NotAllowed(Action::Hit))
+        );
+        assert_eq!(game.player_hand().cards.len(), 2);
+    }
+
+    /// Test late surrender
+    ///
+    /// Surrenders a hard 16, asserts the result is a surrender against a dealer 20,
+    /// and a full loss against a dealer blackjack
+    #[test]
+    fn test_late_surrender() {
+        let mut game = Game::new(1000, TableRules::atlantic_city());
+        game.player_hands[0].bet = 100;
+
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::TEN));
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::SIX));
+
+        assert!(game.allowed_actions().contains(&Action::Surrender));
+        game.surrender();
+        assert!(game.hand_finished());
+        assert!(!game.dealer_must_play());
+
+        game.dealer_hand.add_card(Card::new(Suit::CLUBS, Rank::ACE));
+        game.dealer_hand
+            .add_card(Card::new(Suit::CLUBS, Rank::NINE));
+        assert_eq!(game.determine_winner(), vec![RoundResult::Surrender]);
+        assert_eq!(game.surrender_refund(100), 50);
+
+        game.dealer_hand.cards[1] = Card::new(Suit::CLUBS, Rank::KING);
+        assert_eq!(game.determine_winner(), vec![RoundResult::DealerWin]);
+    }
+
+    /// Test early surrender
+    ///
+    /// Surrenders against a dealer blackjack under early surrender and asserts half the bet is saved
+    #[test]
+    fn test_early_surrender() {
+        let mut game = Game::new(1000, TableRules::default());
+        game.rules.surrender = SurrenderRule::Early;
+
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::TEN));
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::SIX));
+        game.surrender();
+
+        game.dealer_hand.add_card(Card::new(Suit::CLUBS, Rank::ACE));
+        game.dealer_hand
+            .add_card(Card::new(Suit::CLUBS, Rank::KING));
+
+        assert_eq!(game.determine_winner(), vec![RoundResult::Surrender]);
+    }
+
+    /// Test surrender availability
+    ///
+    /// Asserts surrender is unavailable when the table forbids it, and after the first decision
+    #[test]
+    fn test_surrender_availability() {
+        let mut game = Game::new(1000, TableRules::default());
+
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::TEN));
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::SIX));
+        assert!(!game.can_surrender());
+
+        game.rules.surrender = SurrenderRule::Late;
+        assert!(game.can_surrender());
+
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::TWO));
+        assert!(!game.can_surrender());
+    }
+
+    /// Test insurance
+    ///
+    /// Places more insurance than allowed against a dealer ace, asserts it is capped
+    /// at half the bet, and that it only wins against a dealer blackjack
+    #[test]
+    fn test_insurance() {
+        let mut game = Game::new(1000, TableRules::default());
+        game.bet = 100;
+        game.player_hands = vec![PlayerHand::new(100)];
+
+        game.dealer_hand.add_card(Card::new(Suit::CLUBS, Rank::ACE));
+        assert!(game.insurance_offered());
+
+        game.take_insurance(80);
+        assert_eq!(game.insurance, 50);
+
+        game.dealer_hand
+            .add_card(Card::new(Suit::CLUBS, Rank::NINE));
+        assert!(!game.insurance_won());
+
+        game.dealer_hand.cards[1] = Card::new(Suit::CLUBS, Rank::QUEEN);
+        assert!(game.insurance_won());
+    }
+
+    /// Test even money
+    ///
+    /// Takes even money on a blackjack against a dealer ace, asserts a 1:1 win even when the dealer has blackjack
+    #[test]
+    fn test_even_money() {
+        let mut game = Game::new(1000, TableRules::default());
+
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::ACE));
+        game.player_hand_mut()
+            .add_card(Card::new(Suit::HEARTS, Rank::KING));
+        game.dealer_hand.add_card(Card::new(Suit::CLUBS, Rank::ACE));
+
+        game.take_even_money();
+        assert!(game.even_money);
+
+        game.dealer_hand
+            .add_card(Card::new(Suit::CLUBS, Rank::JACK));
+        assert_eq!(game.determine_winner(), vec![RoundResult::PlayerWin]);
+    }
+
+    /// Test hole card deal
+    ///
+    /// Asserts the dealer gets a hidden hole card at an American table, and only one card
+    /// at a no-hole-card table until the hole card is "revealed"
+    #[test]
+    fn test_hole_card() {
+        let mut game = Game::new(1000, TableRules::default());
+        game.bet = 10;
+        game.initial_deal().unwrap();
+
+        assert_eq!(game.dealer_hand.cards.len(), 2);
+        assert!(game.hole_card_hidden());
+        game.reveal_hole_card().unwrap();
+        assert!(!game.hole_card_hidden());
+        assert_eq!(game.dealer_hand.cards.len(), 2);
+
+        let mut game = Game::new(1000, TableRules::european());
+        game.bet = 10;
+        game.initial_deal().unwrap();
+
+        assert_eq!(game.dealer_hand.cards.len(), 1);
+        assert!(!game.dealer_peeks());
+        game.reveal_hole_card().unwrap();
+        assert_eq!(game.dealer_hand.cards.len(), 2);
+    }
+
+    /// Test dealer peek
+    ///
+    /// Asserts the dealer peeks under a ten or ace but not a six,
+    /// and that the round ends on a dealer blackjack
+    #[test]
+    fn test_dealer_peek() {
+        let mut game = Game::new(1000, TableRules::default());
+
+        game.dealer_hand
+            .add_card(Card::new(Suit::CLUBS, Rank::KING));
+        game.dealer_hand
+            .add_card(Card::new(Suit::CLUBS, Rank::NINE));
+        assert!(game.dealer_peeks());
+        assert!(!game.peek_for_blackjack());
+        assert!(game.hole_card_hidden());
+
+        game.dealer_hand.cards[1] = Card::new(Suit::CLUBS, Rank::ACE);
+        assert!(game.peek_for_blackjack());
+        assert!(!game.hole_card_hidden());
+
+        game.dealer_hand.cards[0] = Card::new(Suit::CLUBS, Rank::SIX);
+        assert!(!game.dealer_peeks());
+    }
+
+    /// Test shuffling between rounds
+    ///
+    /// Plays rounds from a single-deck shoe until the cut card comes out,
+    /// and asserts the next deal reports a shuffle and starts from a full deck
+    #[test]
+    fn test_shuffle_between_rounds() {
+        let mut game = Game::new(1000, TableRules::default());
+        game.bet = 10;
+
+        assert!(!game.initial_deal().unwrap());
+        while !game.shoe().needs_shuffle() {
+            game.deal_to_player().unwrap();
+        }
+
+        assert!(game.initial_deal().unwrap());
+        assert_eq!(game.shoe().remaining(), 48);
+    }
+
+    /// Test seeded games
+    ///
+    /// Creates two games from the same seed and asserts they deal identical rounds
+    #[test]
+    fn test_seeded_game() {
+        let mut g1 = Game::with_seed(1000, TableRules::vegas_strip(), 2026);
+        let mut g2 = Game::with_seed(1000, TableRules::vegas_strip(), 2026);
+
+        for _ in 0..10 {
+            g1.initial_deal().unwrap();
+            g2.initial_deal().unwrap();
+
+            assert_eq!(g1.player_hands, g2.player_hands);
+            assert_eq!(g1.dealer_hand, g2.dealer_hand);
+        }
+        assert_eq!(g1.seed(), 2026);
+    }
+
+    /// Stacks a shoe from card codes and starts a game with a $100 bet dealt from it
+    ///
+    /// Cards are dealt player, dealer up card, player, dealer hole card, then in order of play
+    fn scripted_game(rules: TableRules, cards: &str) -> Game {
+        let mut game = Game::scripted(rules, cards);
+        game.bet = 100;
+        game.initial_deal().unwrap();
+
+        game
+    }
+
+    /// Plays the dealer's hand out after the player is done
+    fn play_dealer(game: &mut Game) {
+        game.reveal_hole_card().unwrap();
+        while game.dealer_should_hit() {
+            game.deal_to_dealer().unwrap();
+        }
+    }
+
+    /// Test a scripted round with dealer draws
+    ///
+    /// Player stands on 17, dealer draws from 16 to 21, asserts dealer win
+    #[test]
+    fn test_scripted_dealer_draw() {
+        let mut game = scripted_game(TableRules::default(), "TH 6S 7C TD 5H");
+
+        assert_eq!(game.player_hand().value(), 17);
+        assert!(!game.next_hand().unwrap());
+
+        play_dealer(&mut game);
+
+        assert_eq!(game.dealer_hand.value(), 21);
+        assert_eq!(game.determine_winner(), vec![RoundResult::DealerWin]);
+    }
+
+    /// Test a scripted split and double
+    ///
+    /// Player splits eights against a dealer 6, doubles the first hand, stands on the second,
+    /// and the dealer busts, asserts both hands win with their own bets
+    #[test]
+    fn test_scripted_split_and_double() {
+        let mut game = scripted_game(TableRules::default(), "8H 6S 8C TD 3H KH 2C TS 9S");
+
+        game.split().unwrap();
+        assert_eq!(game.player_hand().value(), 11);
+        game.double_down().unwrap();
+        assert_eq!(game.player_hand().value(), 21);
+        assert!(game.hand_finished());
+
+        assert!(game.next_hand().unwrap());
+        assert_eq!(game.player_hand().value(), 10);
+        game.deal_to_player().unwrap();
+        assert_eq!(game.player_hand().value(), 20);
+        assert!(!game.next_hand().unwrap());
+
+        play_dealer(&mut game);
+
+        assert!(game.dealer_bust());
+        assert_eq!(
+            game.determine_winner(),
+            vec![RoundResult::PlayerWin, RoundResult::PlayerWin]
+        );
+        assert_eq!(game.player_hands[0].bet, 200);
+        assert_eq!(game.player_hands[1].bet, 100);
+    }
+
+    /// Test a scripted dealer blackjack under an ace
+    ///
+    /// Dealer peeks and finds blackjack, asserts the round ends before the player acts
+    /// and insurance pays
+    #[test]
+    fn test_scripted_dealer_blackjack() {
+        let mut game = scripted_game(TableRules::default(), "9H AS 9C KD");
+
+        assert!(game.insurance_offered());
+        game.take_insurance(50);
+        assert!(game.peek_for_blackjack());
+        assert!(game.insurance_won());
+        assert_eq!(game.determine_winner(), vec![RoundResult::DealerWin]);
+    }
+
+    /// Test serializing a game
+    ///
+    /// Stops a seeded game in the middle of a round, round-trips it through JSON,
+    /// and asserts the copy is equal and plays on exactly like the original
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_game() {
+        use crate::{round::Phase, strategy::MimicDealer};
+
+        let mut game = Game::with_seed(1000, TableRules::vegas_strip(), 4);
+        while game.phase() != Phase::PlayerTurn {
+            game.play_round(&mut MimicDealer).unwrap();
+            game.apply(Action::Bet(10)).unwrap();
+            while game.phase() == Phase::Dealing {
+                game.apply(Action::Decline).unwrap();
+            }
+        }
+
+        let json = serde_json::to_string(&game).unwrap();
+        let mut copy: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(copy, game);
+
+        copy.apply(Action::Stand).unwrap();
+        game.apply(Action::Stand).unwrap();
+        for _ in 0..50 {
+            assert_eq!(
+                copy.play_round(&mut MimicDealer),
+                game.play_round(&mut MimicDealer)
+            );
+        }
     }
 }
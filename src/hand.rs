@@ -8,6 +8,7 @@ use std::fmt;
 
 /// Uses vector of `Cards` a player's or dealer's hand containing multiple playing cards
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hand {
     pub cards: Vec<Card>,
 }
@@ -57,6 +58,24 @@ impl Hand {
 
         total_value
     }
+
+    /// Check if the hand is soft (contains an Ace still counted as 11)
+    pub fn is_soft(&self) -> bool {
+        let hard_value: u8 = self
+            .cards
+            .iter()
+            .map(|c| if c.rank() == &Rank::ACE { 1 } else { c.value() })
+            .sum();
+        let has_ace = self.cards.iter().any(|c| c.rank() == &Rank::ACE);
+
+        has_ace && hard_value + 10 <= 21
+    }
+}
+
+impl Default for Hand {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl fmt::Display for Hand {
@@ -69,7 +88,6 @@ impl fmt::Display for Hand {
 }
 
 #[cfg(test)]
-
 mod tests {
     use super::*;
     use crate::card::Suit;
@@ -103,7 +121,7 @@ mod tests {
         h.add_card(ace);
 
         assert_eq!(h.value(), 21);
-        assert_eq!(h.is_blackjack(), true);
+        assert!(h.is_blackjack());
 
         h.add_card(six);
 
@@ -113,4 +131,26 @@ mod tests {
 
         assert_eq!(h.value(), 18);
     }
+
+    /// Test `is_soft()` method
+    ///
+    /// Builds soft and hard hands, including one where the Ace is forced down to 1
+    #[test]
+    fn test_is_soft() {
+        let mut h = Hand::new();
+
+        h.add_card(Card::new(Suit::HEARTS, Rank::ACE));
+        h.add_card(Card::new(Suit::CLUBS, Rank::SIX));
+        assert!(h.is_soft());
+        assert_eq!(h.value(), 17);
+
+        h.add_card(Card::new(Suit::SPADES, Rank::NINE));
+        assert!(!h.is_soft());
+        assert_eq!(h.value(), 16);
+
+        let mut hard = Hand::new();
+        hard.add_card(Card::new(Suit::CLUBS, Rank::TEN));
+        hard.add_card(Card::new(Suit::CLUBS, Rank::SEVEN));
+        assert!(!hard.is_soft());
+    }
 }
@@ -0,0 +1,295 @@
+//! # Hand History
+//!
+//! Records every round of a session as JSON lines, and reads them back for replays.
+//!
+//! The first line holds the game as the session started, every later line one round:
+//! the bankroll before it, then each action taken (the bet first) with the events it caused,
+//! which include every card dealt, dealer draws, results and payouts.
+//! Replaying the actions against the starting game reproduces the session exactly.
+
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    card::Card,
+    game::{Action, Game},
+    hand::Hand,
+    round::{Event, GameError, Offer},
+    strategy::{Strategy, TableView},
+};
+
+/// Version written to the session line of new histories
+///
+/// Histories of other versions are refused, since their events no longer match what the game produces
+pub const HISTORY_VERSION: u32 = 2;
+
+/// One line of a hand history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    /// The first line: the game as the session started
+    Session {
+        version: u32,
+        seed: u64,
+        game: Box<Game>,
+    },
+    /// A round played to the end
+    Round(RoundRecord),
+}
+
+/// Everything that happened in one round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoundRecord {
+    /// Number of the round in the session, from 1
+    pub round: u16,
+    /// Bankroll before the bet
+    pub bankroll: u32,
+    pub steps: Vec<Step>,
+}
+
+/// An action and the events it caused
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub action: Action,
+    pub events: Vec<Event>,
+}
+
+/// Plays like `player`, writing a line to `writer` for every finished round
+///
+/// Writing stops at the first error, which `error()` returns, but play goes on
+pub struct Recorder<S, W: Write> {
+    pub player: S,
+    writer: W,
+    /// The action chosen last, waiting for its events
+    action: Option<Action>,
+    round: Option<RoundRecord>,
+    error: Option<io::Error>,
+}
+
+impl<S, W: Write> Recorder<S, W> {
+    /// Constructor: Records the session of `game` played by `player`, starting with its session line
+    pub fn new(player: S, mut writer: W, game: &Game) -> Self {
+        let session = Record::Session {
+            version: HISTORY_VERSION,
+            seed: game.seed(),
+            game: Box::new(game.clone()),
+        };
+        let error = write_record(&mut writer, &session).err();
+
+        Recorder {
+            player,
+            writer,
+            action: None,
+            round: None,
+            error,
+        }
+    }
+
+    /// The error that stopped the recording, if any
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    /// Remember `action` until its events arrive
+    fn chose(&mut self, action: Action) -> Action {
+        self.action = Some(action);
+        action
+    }
+
+    /// Add the events of the last action to the round, and write the round once it is over
+    fn record(&mut self, events: &[Event]) {
+        let (Some(action), Some(round)) = (self.action.take(), self.round.as_mut()) else {
+            return;
+        };
+        round.steps.push(Step {
+            action,
+            events: events.to_vec(),
+        });
+
+        if !events
+            .iter()
+            .any(|event| matches!(event, Event::RoundOver { .. }))
+        {
+            return;
+        }
+        if let Some(round) = self.round.take()
+            && self.error.is_none()
+            && let Err(e) = write_record(&mut self.writer, &Record::Round(round))
+        {
+            self.error = Some(e);
+        }
+    }
+}
+
+impl<S: Strategy, W: Write> Strategy for Recorder<S, W> {
+    fn choose_bet(&mut self, table: &TableView) -> u32 {
+        let bet = self.player.choose_bet(table);
+
+        self.round = Some(RoundRecord {
+            round: table.round.saturating_add(1),
+            bankroll: table.bankroll,
+            steps: Vec::new(),
+        });
+        self.chose(Action::Bet(bet));
+        bet
+    }
+
+    fn choose_action(&mut self, hand: &Hand, dealer_upcard: Card, allowed: &[Action]) -> Action {
+        let action = self.player.choose_action(hand, dealer_upcard, allowed);
+        self.chose(action)
+    }
+
+    fn choose_offer(
+        &mut self,
+        offer: Offer,
+        hand: &Hand,
+        dealer_upcard: Card,
+        table: &TableView,
+    ) -> Action {
+        let action = self.player.choose_offer(offer, hand, dealer_upcard, table);
+        self.chose(action)
+    }
+
+    fn observe(&mut self, game: &Game, events: &[Event]) {
+        self.record(events);
+        self.player.observe(game, events);
+    }
+}
+
+/// Write a record as one line of JSON
+fn write_record(writer: &mut impl Write, record: &Record) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// Read a hand history: the game the session started with, and its rounds
+///
+/// Refuses histories of another `HISTORY_VERSION`
+pub fn read(reader: impl BufRead) -> io::Result<(Game, Vec<RoundRecord>)> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let mut lines = reader.lines();
+
+    let first = lines
+        .next()
+        .ok_or_else(|| invalid("hand history is empty".to_string()))??;
+    let game = match serde_json::from_str(&first)? {
+        Record::Session { version, .. } if version != HISTORY_VERSION => {
+            return Err(invalid(format!(
+                "hand history version {} can't be replayed by this version, which records version {}",
+                version, HISTORY_VERSION
+            )));
+        }
+        Record::Session { game, .. } => *game,
+        Record::Round(_) => return Err(invalid("hand history has no session line".to_string())),
+    };
+
+    let mut rounds = Vec::new();
+    for line in lines {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Record::Round(round) = serde_json::from_str(&line)? {
+            rounds.push(round);
+        }
+    }
+
+    Ok((game, rounds))
+}
+
+/// Reasons a replay can stop
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayError {
+    /// The game rejected a recorded action
+    Game(GameError),
+    /// The game produced different events than recorded in round `round`
+    Diverged { round: u16 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Game(e) => write!(f, "a recorded action was rejected: {}", e),
+            ReplayError::Diverged { round } => {
+                write!(f, "round {} played out differently than recorded", round)
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl Game {
+    /// Apply a recorded step, checking the game produces the recorded events
+    pub fn replay_step(&mut self, round: u16, step: &Step) -> Result<(), ReplayError> {
+        let events = self.apply(step.action).map_err(ReplayError::Game)?;
+
+        if events == step.events {
+            Ok(())
+        } else {
+            Err(ReplayError::Diverged { round })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{basic_strategy::BasicStrategy, rules::TableRules};
+
+    /// Test recording and replaying a session
+    ///
+    /// Records rounds of basic strategy, reads the history back, and asserts every step
+    /// replays against the starting game with the recorded events
+    #[test]
+    fn test_record_and_replay() {
+        let mut game = Game::with_seed(1000, TableRules::atlantic_city(), 8);
+        let strategy = BasicStrategy::new(&game.rules);
+        let mut recorder = Recorder::new(strategy, Vec::new(), &game);
+
+        for _ in 0..40 {
+            game.play_round(&mut recorder).unwrap();
+        }
+        assert!(recorder.error().is_none());
+
+        let (mut replay, rounds) = read(recorder.writer.as_slice()).unwrap();
+        assert_eq!(rounds.len(), 40);
+        assert_eq!(rounds[0].round, 1);
+        assert_eq!(rounds[0].bankroll, 1000);
+        assert!(matches!(rounds[0].steps[0].action, Action::Bet(_)));
+
+        for round in &rounds {
+            for step in &round.steps {
+                replay.replay_step(round.round, step).unwrap();
+            }
+        }
+        assert_eq!(replay.bankroll, game.bankroll);
+        assert_eq!(replay.i, 40);
+    }
+
+    /// Test a history that doesn't match the game
+    ///
+    /// Tampers with a recorded card and asserts the replay reports the divergence
+    #[test]
+    fn test_replay_diverged() {
+        let mut game = Game::with_seed(1000, TableRules::vegas_strip(), 2);
+        let mut recorder = Recorder::new(BasicStrategy::new(&game.rules), Vec::new(), &game);
+        game.play_round(&mut recorder).unwrap();
+
+        let (mut replay, mut rounds) = read(recorder.writer.as_slice()).unwrap();
+        rounds[0].steps[0]
+            .events
+            .retain(|event| !matches!(event, Event::CardDealt { .. }));
+
+        assert_eq!(
+            replay.replay_step(1, &rounds[0].steps[0]),
+            Err(ReplayError::Diverged { round: 1 })
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! # Blackjack Engine
+//!
+//! The reusable core of the blackjack CLI: cards, decks, hands and the game rules.
+//!
+//! Everything needed to play a round of blackjack lives in this library, so
+//! simulators, bots and servers can depend on it without pulling in the terminal front end.
+//!
+//! ## Modules
+//! * [`basic_strategy`] — `BasicStrategy`, a bot whose tables are computed from the table rules
+//! * [`betting`] — the `BetStrategy` trait, flat, count ramp, Martingale, Paroli and Kelly betting, and `Betting`
+//! * [`card`] — `Card`, `Suit` and `Rank`
+//! * [`counting`] — `CountingSystem` presets (Hi-Lo, KO, Omega II, Zen) and `Counter`, the running and true count of a shoe
+//! * [`dealer_odds`] — `DealerOdds`, exact probabilities of how the dealer finishes for a shoe composition
+//! * [`deck`] — `Deck`, a single shuffled pack of 52 cards
+//! * [`save`] — `Game::to_save()` and `Game::from_save()`, a versioned text format to resume sessions from, and `SessionStats::to_save()` to keep their statistics
+//! * [`shoe`] — `Shoe`, several decks dealt from with a cut card, and `Composition`, the cards left in it
+//! * [`history`] — `Recorder`, which writes every round to a JSON lines hand history, and replaying one (`history` feature)
+//! * [`hand`] — `Hand`, the cards held by the player or dealer
+//! * [`expected_value`] — exact, composition-dependent expected value of every action, and `Game::action_values()`
+//! * [`game`] — `Game`, `PlayerHand`, `Action` and `RoundResult`, the session state and win logic
+//! * [`round`] — `Phase`, `Event` and `Game::apply()`, the round engine any front end can drive
+//! * [`rules`] — `TableRules`, the configurable rules of the table and named presets
+//! * [`simulation`] — `Simulation`, headless play of many rounds, across threads, to measure a strategy's expected value or a betting system's risk of ruin
+//! * [`stats`] — `SessionStats`, the tally of a session: results, busts, doubles, splits, biggest swings and streaks
+//! * [`strategy`] — the `Strategy` trait for pluggable decision makers, and `Game::play_round()`
+//!
+//! ## Features
+//! * `history` (default) — hand histories in [`history`], using `serde` and `serde_json`
+//! * `serde` — `Serialize` and `Deserialize` for the game state: `Card`, `Hand`, `Deck`, `Shoe`, `Game`,
+//!   `TableRules`, `Action`, `Event` and `RoundResult`, with cards written as plain codes like `"AS"`
+//!
+//! ## Example
+//! ```
+//! use blackjack::{Action, Event, Game, Phase, TableRules};
+//!
+//! let mut game = Game::new(1000, TableRules::vegas_strip());
+//! game.apply(Action::Bet(10)).unwrap();
+//!
+//! // Decline any insurance, then hit until 17 like the dealer does
+//! let events = loop {
+//!     let action = match game.phase() {
+//!         Phase::Dealing => Action::Decline,
+//!         Phase::PlayerTurn if game.player_hand().value() < 17 => Action::Hit,
+//!         Phase::PlayerTurn => Action::Stand,
+//!         _ => break Vec::new(),
+//!     };
+//!     let events = game.apply(action).unwrap();
+//!
+//!     if game.phase() == Phase::Settlement {
+//!         break events;
+//!     }
+//! };
+//!
+//! assert_eq!(game.phase(), Phase::Settlement);
+//! # let _ = events;
+//! ```
+
+pub mod basic_strategy;
+pub mod betting;
+pub mod card;
+pub mod counting;
+pub mod dealer_odds;
+pub mod deck;
+pub mod expected_value;
+pub mod game;
+pub mod hand;
+#[cfg(feature = "history")]
+pub mod history;
+pub mod round;
+pub mod rules;
+pub mod save;
+pub mod shoe;
+pub mod simulation;
+pub mod stats;
+pub mod strategy;
+
+pub use basic_strategy::{ActionValues, BasicStrategy};
+pub use betting::{BetStrategy, Betting};
+pub use card::{Card, ParseCardError, Rank, Suit, parse_cards, parse_hand};
+pub use counting::{Counter, CountingSystem};
+pub use dealer_odds::DealerOdds;
+pub use deck::Deck;
+pub use game::{Action, Game, PlayerHand, RoundResult};
+pub use hand::Hand;
+pub use round::{Event, GameError, Offer, Phase, Seat};
+pub use rules::TableRules;
+pub use save::LoadError;
+pub use shoe::{Composition, Shoe};
+pub use simulation::{BankrollReport, RunningStats, Simulation, SimulationReport};
+pub use stats::SessionStats;
+pub use strategy::{MimicDealer, Strategy, TableView};
@@ -3,141 +3,359 @@
 //! A terminal-based implementation of Blackjack written in Rust.
 //!
 //! ## Architecture
-//! * **Game Loop:** The `main` function handles the game flow (rounds, betting, dealing, turns, and round result).
-//! * **Modules:** Relies on `card`, `deck`, `hand`, `game`, and `user_interface` for game logic.
+//! * **Game Loop:** The `main` function hands each round to `Game::play_round()`, which asks the chosen
+//!   `Strategy` for every decision and runs the round (dealing, turns, and round result).
+//! * **Library:** Game logic (`card`, `deck`, `shoe`, `hand`, `game`, `round`, `strategy`, `counting`, `betting`) lives in the `blackjack` library crate.
+//! * **Modules:** `user_interface` handles all terminal input and output, including the interactive `TerminalPlayer`,
+//!   `coach` grades the player's decisions against basic strategy in `--coach` mode,
+//!   `trainer` quizzes the player on the running count in `--count` mode,
+//!   `score` keeps the tally of right answers both of those modes show,
+//!   `profile` keeps each named player's lifetime statistics and preferred modes,
+//!   and `storage` saves the session, hand histories and profile under the user's data directory.
 //!
 //! ## Usage
 //! Run with `cargo run`. Follow the prompts to bet and play.
+//! Pick a table with `cargo run -- --rules vegas-strip`, and replay a session
+//! exactly with the seed shown in the header: `cargo run -- --seed <number>`.
+//! Your bankroll and shoe are saved after every round, and you are offered to resume on the next start.
+//! Every session is recorded to a hand history; step through the last one with `cargo run -- replay`.
+//! Players sharing a machine pick a profile at startup, or pass it: `cargo run -- --profile <name>`.
+//! Watch a bot play instead with `cargo run -- --player basic-strategy`,
+//! or learn basic strategy with hints and mistake flags: `cargo run -- --coach`.
+//! Analyse close decisions with the exact EV of each action: `cargo run -- --show-ev`,
+//! or practice counting cards: `cargo run -- --count hi-lo`.
+//! Measure the house edge of a table with `cargo run --release -- simulate --rules european --rounds 1000000`,
+//! and compare betting systems' risk of ruin with `cargo run --release -- bets --bankroll 500`.
 
-mod card;
-mod deck;
-mod game;
-mod hand;
+mod cli;
+mod coach;
+mod profile;
+mod score;
+mod storage;
+mod trainer;
 mod user_interface;
 
-use game::{Game, RoundResult};
+use blackjack::{
+    BasicStrategy, BetStrategy, Betting, CountingSystem, Game, MimicDealer, SessionStats,
+    Simulation, Strategy, TableRules, betting,
+    history::{self, Recorder},
+};
+use cli::{Command, Options, PlayerKind};
+use profile::{DEFAULT_PROFILE, Preferences, Profile, STARTING_BANKROLL};
+use std::{fs::File, io::BufReader, path::PathBuf};
 use user_interface as ui;
 
-/// Initializes the game, handles the main loop, and manages user input
-/// until the player runs out of money or chooses to quit.
+/// Initializes the game, handles the main loop, and lets the chosen player make decisions
+/// until they run out of money or choose to quit.
 fn main() {
-    let mut game = Game::new(1000);
+    let mut options = Options::from_args();
+    match options.command {
+        Command::Simulate { rounds, threads } => return simulate(options, rounds, threads),
+        Command::Bets {
+            rounds,
+            sessions,
+            bankroll,
+            threads,
+        } => return compare_bets(options, rounds, sessions, bankroll, threads),
+        Command::Replay { path } => return replay(path, options.profile.as_deref()),
+        Command::Play => {}
+    }
+
+    let human = options.player == PlayerKind::Human;
+    let mut profile = human.then(|| open_profile(&mut options));
+    let name = profile
+        .as_ref()
+        .map_or(DEFAULT_PROFILE, |profile| profile.name.as_str())
+        .to_string();
+    let bankroll = profile.as_ref().map_or(STARTING_BANKROLL, |profile| {
+        profile.starting_bankroll(options.rules.min_bet)
+    });
+    let (mut game, earlier) = match human.then(|| resume(&name, options.new_table)).flatten() {
+        Some(saved) => saved,
+        None => match options.seed {
+            Some(seed) => (
+                Game::with_seed(bankroll, options.rules, seed),
+                SessionStats::default(),
+            ),
+            None => (Game::new(bankroll, options.rules), SessionStats::default()),
+        },
+    };
+    let bets = options
+        .bets
+        .as_deref()
+        .and_then(|name| bet_strategy(name, &game.rules, options.count.as_ref()));
+    let mut player: Box<dyn Strategy> = match options.player {
+        PlayerKind::Human => Box::new(ui::TerminalPlayer::new(
+            name.clone(),
+            options.coach.then(|| coach::Coach::new(&game.rules)),
+            options.show_ev,
+            options
+                .count
+                .map(|system| trainer::Trainer::new(system, game.rules.decks)),
+            bets,
+        )),
+        PlayerKind::MimicDealer => spectate(MimicDealer, bets),
+        PlayerKind::BasicStrategy => spectate(BasicStrategy::new(&game.rules), bets),
+    };
+
+    let save_as = human.then_some(name.as_str());
+    let stats = match storage::create_history(&name, game.seed()) {
+        Ok((path, file)) => {
+            let mut recorder = Recorder::new(player, file, &game);
+            let stats = play(&mut game, &mut recorder, save_as, earlier);
 
-    // Session loop:
-    //
-    // Simulates rounds until user quits or bankroll is 0
-    'session: loop {
-        ui::display_header(game.i, game.bankroll);
+            match recorder.error() {
+                Some(e) => eprintln!("Could not write the hand history: {}", e),
+                None => println!("Hand history saved to {}", path.display()),
+            }
+            stats
+        }
+        Err(e) => {
+            eprintln!("Could not create a hand history: {}", e);
+            play(&mut game, player.as_mut(), save_as, earlier)
+        }
+    };
 
-        let temp_bet: u32 = ui::get_bet(game.bankroll);
-        game.bet = temp_bet;
+    if let Some(profile) = &mut profile {
+        profile.record(&stats, game.bankroll);
+        if let Err(e) = storage::save_profile(profile) {
+            eprintln!("Could not save the profile: {}", e);
+        }
+        ui::print_stats(
+            &format!("LIFETIME: {}", profile.name.to_uppercase()),
+            &profile.stats,
+        );
+    }
+}
 
-        game.initial_deal();
+/// Picks the profile to play as, from `--profile` or else a prompt
+///
+/// Modes given on the command line become the profile's preferences; without any,
+/// the modes it played with last time are used again
+fn open_profile(options: &mut Options) -> Profile {
+    let name = options
+        .profile
+        .clone()
+        .unwrap_or_else(|| ui::choose_profile(&storage::profile_names()));
+    let mut profile = storage::load_profile(&name).unwrap_or_else(|e| {
+        eprintln!("Could not load the profile '{}': {}\n", name, e);
+        Profile::new(&name)
+    });
 
-        // Gameplay loop:
-        //
-        // Handles player and dealer turns, checks for busts and blackjacks
-        'gameplay: loop {
-            println!();
-            ui::show_hands(&game.player_hand, &game.dealer_hand);
+    if options.preferences_given {
+        profile.preferences = Preferences::of(options);
+    } else {
+        profile.preferences.apply_to(options);
+    }
+    profile
+}
 
-            if game.player_hand.is_blackjack() {
-                break 'gameplay;
-            }
+/// Session loop: plays rounds until the user quits or the bankroll is 0, then prints the session summary
+///
+/// The summary counts on from the `earlier` statistics of a resumed session. With `save_as`, the
+/// session is saved to that profile after every round. Returns the statistics of the rounds played
+/// this time, as the earlier ones were already added to the profile
+fn play(
+    game: &mut Game,
+    player: &mut dyn Strategy,
+    save_as: Option<&str>,
+    earlier: SessionStats,
+) -> SessionStats {
+    let mut session = earlier;
+    let mut stats = SessionStats::default();
 
-            // --- Player turn loop ---
-            'player_turn: loop {
-                if game.player_bust() {
-                    break 'gameplay;
-                }
-
-                if ui::player_hits() {
-                    game.deal_to_player();
-                    println!();
-                    ui::show_hands(&game.player_hand, &game.dealer_hand);
-                    continue;
-                } else {
-                    break 'player_turn;
-                }
+    loop {
+        match game.play_round(player) {
+            Ok(events) => {
+                session.record(game, &events);
+                stats.record(game, &events);
             }
+            Err(e) => {
+                println!("\n**{}**\n", e);
+                break;
+            }
+        }
 
-            game.deal_to_dealer();
+        if let Some(profile) = save_as {
+            autosave(profile, game, &session);
+        }
+        if !ui::play_again(game.bankroll, game.rules.min_bet) {
+            break;
+        }
+    }
 
-            println!("\n\n=== Dealer's Turn ===\n\n");
-            print!("    ");
-            ui::show_hands(&game.player_hand, &game.dealer_hand);
+    ui::print_stats("SESSION SUMMARY", &session);
+    stats
+}
 
-            // --- Dealer turn loop ---
-            loop {
-                if game.dealer_hand.is_blackjack() {
-                    break 'gameplay;
-                }
+/// Runs the `replay` command: steps through the hand history at `path`, or the latest one of `profile`,
+/// replaying every action against the recorded game and showing it as it was played
+fn replay(path: Option<PathBuf>, profile: Option<&str>) {
+    let profile = profile.unwrap_or(DEFAULT_PROFILE);
+    let Some(path) = path.or_else(|| storage::latest_history(profile)) else {
+        return eprintln!("There is no hand history to replay");
+    };
+    let (mut game, rounds) =
+        match File::open(&path).and_then(|file| history::read(BufReader::new(file))) {
+            Ok(history) => history,
+            Err(e) => return eprintln!("Could not read {}: {}", path.display(), e),
+        };
 
-                if game.dealer_hand.value() >= 17 {
-                    break 'gameplay;
-                }
+    for round in &rounds {
+        ui::display_header(
+            round.round.saturating_sub(1),
+            round.bankroll,
+            &game.rules,
+            game.seed(),
+            None,
+            None,
+            None,
+        );
 
-                println!("\n    Dealer hits...");
-                game.deal_to_dealer();
-                print!("    ");
-                ui::show_hands(&game.player_hand, &game.dealer_hand);
+        for step in &round.steps {
+            ui::print_recorded_action(step.action);
+            if let Err(e) = game.replay_step(round.round, step) {
+                return eprintln!("\n**Replay stopped: {}**", e);
+            }
+            ui::show_events(&game, &step.events, false);
 
-                if game.dealer_bust() {
-                    break 'gameplay;
-                }
+            if !ui::next_step() {
+                return;
             }
         }
+    }
 
-        println!();
+    println!(
+        "\nEnd of the hand history: {} rounds, bankroll ${}",
+        rounds.len(),
+        game.bankroll
+    );
+}
 
-        // --- Winner determination ---
-        //
-        // Determines winner, updates bankroll, and displays outcome
-        let result = game.determine_winner();
+/// Offers to resume the saved session of `profile`, if there is one. Returns the game to resume
+/// and its statistics so far
+///
+/// With `new_table` (`--rules` or `--seed` given), the save is not offered, and the player is told
+/// the new session will replace it
+fn resume(profile: &str, new_table: bool) -> Option<(Game, SessionStats)> {
+    match storage::load(profile)? {
+        Ok((saved, _)) if saved.bankroll >= saved.rules.min_bet && new_table => {
+            ui::print_save_skipped(&saved);
+            None
+        }
+        Ok((saved, stats)) if saved.bankroll >= saved.rules.min_bet => {
+            ui::resume_session(&saved).then_some((saved, stats))
+        }
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!("Could not resume the saved session: {}\n", e);
+            None
+        }
+    }
+}
+
+/// Saves the session of `profile` and its statistics after a round, or deletes the save once the player is out of money
+fn autosave(profile: &str, game: &Game, stats: &SessionStats) {
+    let result = if game.bankroll < game.rules.min_bet {
+        storage::delete(profile)
+    } else {
+        storage::save(profile, game, stats)
+    };
+
+    if let Err(e) = result {
+        eprintln!("Could not save the session: {}", e);
+    }
+}
+
+/// Lets a bot play at the terminal, with its bets chosen by `bets` if given
+fn spectate<S: Strategy + 'static>(
+    bot: S,
+    bets: Option<Box<dyn BetStrategy>>,
+) -> Box<dyn Strategy> {
+    match bets {
+        Some(bets) => Box::new(ui::Spectator(Betting::new(bot, bets))),
+        None => Box::new(ui::Spectator(bot)),
+    }
+}
+
+/// Looks up a betting system by name, counting with `count` (Hi-Lo if not given) if it needs a count
+fn bet_strategy(
+    name: &str,
+    rules: &TableRules,
+    count: Option<&CountingSystem>,
+) -> Option<Box<dyn BetStrategy>> {
+    let system = count.cloned().unwrap_or_else(CountingSystem::hi_lo);
+    betting::preset(name, rules, &system)
+}
+
+/// Runs the `bets` command: plays `sessions` sessions of up to `rounds` rounds from `bankroll`
+/// with the chosen bot and every betting system, on `threads` threads, then prints the reports
+fn compare_bets(options: Options, rounds: u64, sessions: u64, bankroll: u32, threads: usize) {
+    let simulation = match options.seed {
+        Some(seed) => Simulation::with_seed(options.rules, rounds, seed),
+        None => Simulation::new(options.rules, rounds),
+    };
+    let basic = BasicStrategy::new(&simulation.rules);
+    let mut reports = Vec::new();
+
+    for &name in betting::preset_names() {
+        let bets = || {
+            bet_strategy(name, &simulation.rules, options.count.as_ref())
+                .expect("every preset name is a betting system")
+        };
+        let result = match options.player {
+            PlayerKind::MimicDealer => simulation.run_sessions(sessions, bankroll, threads, || {
+                Betting::new(MimicDealer, bets())
+            }),
+            _ => simulation.run_sessions(sessions, bankroll, threads, || {
+                Betting::new(basic.clone(), bets())
+            }),
+        };
 
         match result {
-            RoundResult::PlayerWin => {
-                // print player win
-                ui::print_outcome(true, game.bet);
-                game.bankroll += game.bet;
-            }
-            RoundResult::DealerWin => {
-                // print player loss
-                ui::print_outcome(false, game.bet);
-                game.bankroll -= game.bet
-            }
-            RoundResult::PlayerBlackjack => {
-                // change win amount
-                let payout: u32 = (game.bet * 3) / 2;
-                // print blackjack message
-                ui::print_blackjack(payout);
-            }
-            RoundResult::Push => {
-                // print push message
-                ui::print_push();
-            }
+            Ok(report) => reports.push((name, report)),
+            Err(e) => return eprintln!("Simulation stopped: {}", e),
         }
+    }
+
+    ui::print_bankroll_reports(&simulation, options.player.name(), &reports);
+}
 
-        if !ui::play_again(game.bankroll) {
-            break 'session;
+/// Runs the `simulate` command: plays `rounds` rounds with the chosen bot on `threads` threads,
+/// then prints the report
+fn simulate(options: Options, rounds: u64, threads: usize) {
+    let simulation = match options.seed {
+        Some(seed) => Simulation::with_seed(options.rules, rounds, seed),
+        None => Simulation::new(options.rules, rounds),
+    };
+    let result = match options.player {
+        PlayerKind::MimicDealer => simulation.run_parallel(threads, || MimicDealer),
+        _ => {
+            let basic = BasicStrategy::new(&simulation.rules);
+            simulation.run_parallel(threads, || basic.clone())
         }
+    };
+
+    match result {
+        Ok(report) => ui::print_report(&simulation, options.player.name(), &report),
+        Err(e) => eprintln!("Simulation stopped: {}", e),
     }
 }
 
 #[cfg(test)]
-
 mod tests {
     use super::*;
+    use blackjack::{RoundResult, TableRules};
 
     /// Test game initialization
     ///
     /// Creates new game with bankroll of 500, asserts bankroll, bet, and hand values
     #[test]
     fn test_game_initialization() {
-        let game = Game::new(500);
+        let game = Game::new(500, TableRules::default());
         assert_eq!(game.bankroll, 500);
         assert_eq!(game.bet, 0);
-        assert_eq!(game.player_hand.value(), 0);
+        assert_eq!(game.player_hand().value(), 0);
         assert_eq!(game.dealer_hand.value(), 0);
     }
 
@@ -146,12 +364,12 @@ mod tests {
     /// Creates new game, sets bet to 200, simulates blackjack result, and asserts payout amount
     #[test]
     fn test_blackjack_payout() {
-        let mut game = Game::new(1000);
+        let mut game = Game::new(1000, TableRules::default());
         game.bet = 200;
         let result = RoundResult::PlayerBlackjack;
         match result {
             RoundResult::PlayerBlackjack => {
-                let payout: u32 = (game.bet * 3) / 2;
+                let payout: u32 = game.blackjack_payout();
                 assert_eq!(payout, 300);
             }
             _ => panic!("Expected PlayerBlackjack result"),
@@ -0,0 +1,202 @@
+//! # Profile
+//!
+//! Named players sharing a machine. Each profile keeps its own bankroll from session to session,
+//! its saved session and hand histories (see `storage`), lifetime statistics, and the modes it
+//! last played with.
+//!
+//! A profile is stored as plain text, one `key=value` per line like a saved session;
+//! unknown keys are ignored and missing ones start from zero.
+
+use crate::cli::Options;
+use blackjack::{CountingSystem, SessionStats};
+use std::collections::HashMap;
+
+/// Profile played when none is picked; it keeps the files from before profiles existed
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Bankroll a new profile starts with, and starts over from once it can't cover the table minimum
+pub const STARTING_BANKROLL: u32 = 1000;
+
+/// Longest profile name, so it fits in the header
+const MAX_NAME_LEN: usize = 16;
+
+/// The modes a profile plays with unless other ones are given on the command line
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Preferences {
+    pub coach: bool,
+    pub show_ev: bool,
+    /// Name of the counting system to train
+    pub count: Option<String>,
+    /// Name of the betting system choosing the bets
+    pub bets: Option<String>,
+}
+
+impl Preferences {
+    /// The modes chosen in `options`
+    pub fn of(options: &Options) -> Self {
+        Preferences {
+            coach: options.coach,
+            show_ev: options.show_ev,
+            count: options.count.as_ref().map(|system| system.name.clone()),
+            bets: options.bets.clone(),
+        }
+    }
+
+    /// Choose these modes in `options`, skipping names that are no longer known
+    pub fn apply_to(&self, options: &mut Options) {
+        options.coach = self.coach;
+        options.show_ev = self.show_ev;
+        options.count = self.count.as_deref().and_then(CountingSystem::preset);
+        options.bets = self.bets.clone();
+    }
+}
+
+/// A player's lifetime record and preferences
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// Money left at the end of the last session, carried into the next one
+    pub bankroll: u32,
+    /// Sessions finished with this profile
+    pub sessions: u32,
+    /// Statistics over every session
+    pub stats: SessionStats,
+    pub preferences: Preferences,
+}
+
+impl Profile {
+    /// Constructor: A profile that hasn't played yet
+    pub fn new(name: &str) -> Self {
+        Profile {
+            name: name.to_string(),
+            bankroll: STARTING_BANKROLL,
+            sessions: 0,
+            stats: SessionStats::default(),
+            preferences: Preferences::default(),
+        }
+    }
+
+    /// Check if `name` can name a profile: 1 to 16 letters, digits, `-` or `_`
+    pub fn is_valid_name(name: &str) -> bool {
+        !name.is_empty()
+            && name.len() <= MAX_NAME_LEN
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    }
+
+    /// Bankroll to start a new session with at a table with a minimum bet of `min_bet`
+    ///
+    /// A profile that went bust starts over from `STARTING_BANKROLL`
+    pub fn starting_bankroll(&self, min_bet: u32) -> u32 {
+        if self.bankroll >= min_bet {
+            self.bankroll
+        } else {
+            STARTING_BANKROLL
+        }
+    }
+
+    /// Add a finished session that ended with `bankroll` to the lifetime record
+    pub fn record(&mut self, session: &SessionStats, bankroll: u32) {
+        self.bankroll = bankroll;
+        self.sessions += 1;
+        self.stats.merge(session);
+    }
+
+    /// Write the profile as `key=value` lines
+    pub fn to_text(&self) -> String {
+        let prefs = &self.preferences;
+        let mut text = String::from("# rust-blackjack-cli profile\n");
+
+        text.push_str(&format!("bankroll={}\n", self.bankroll));
+        text.push_str(&format!("sessions={}\n", self.sessions));
+        text.push_str(&self.stats.to_save());
+        text.push_str(&format!("prefs.coach={}\n", prefs.coach));
+        text.push_str(&format!("prefs.show_ev={}\n", prefs.show_ev));
+        text.push_str(&format!(
+            "prefs.count={}\n",
+            prefs.count.as_deref().unwrap_or("")
+        ));
+        text.push_str(&format!(
+            "prefs.bets={}\n",
+            prefs.bets.as_deref().unwrap_or("")
+        ));
+        text
+    }
+
+    /// Read a profile written by `to_text()`
+    pub fn from_text(name: &str, text: &str) -> Self {
+        let fields: HashMap<&str, &str> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .collect();
+        let get = |key: &str| fields.get(key).copied().unwrap_or("");
+        let name_of = |key: &str| Some(get(key).to_string()).filter(|value| !value.is_empty());
+
+        let mut profile = Profile::new(name);
+        profile.bankroll = get("bankroll").parse().unwrap_or(STARTING_BANKROLL);
+        profile.sessions = get("sessions").parse().unwrap_or(0);
+        profile.stats = SessionStats::from_save(text).unwrap_or_default();
+        profile.preferences = Preferences {
+            coach: get("prefs.coach") == "true",
+            show_ev: get("prefs.show_ev") == "true",
+            count: name_of("prefs.count"),
+            bets: name_of("prefs.bets"),
+        };
+        profile
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test writing and reading a profile
+    ///
+    /// Records two sessions, asserts the profile reads back the same with the bankroll the last
+    /// one ended with, and that a partial profile starts its missing fields from zero and $1000
+    #[test]
+    fn test_profile_round_trip() {
+        let mut profile = Profile::new("ace");
+        let mut session = SessionStats::default();
+        session.rounds = 12;
+        session.wins = 5;
+        session.biggest_loss = 40;
+        session.longest_win_streak = 3;
+        session.net = -25;
+        profile.record(&session, 975);
+        profile.record(&session, 950);
+        profile.preferences.coach = true;
+        profile.preferences.count = Some("Omega II".to_string());
+
+        let read = Profile::from_text("ace", &profile.to_text());
+        assert_eq!(read, profile);
+        assert_eq!(read.bankroll, 950);
+        assert_eq!(read.starting_bankroll(10), 950);
+        assert_eq!(read.stats.rounds, 24);
+        assert_eq!(read.stats.net, -50);
+        assert_eq!(read.stats.longest_win_streak, 3);
+
+        let partial = Profile::from_text("ace", "sessions=2\nprefs.bets=ramp\n");
+        assert_eq!(partial.sessions, 2);
+        assert_eq!(partial.bankroll, STARTING_BANKROLL);
+        assert_eq!(partial.stats, SessionStats::default());
+        assert_eq!(partial.preferences.bets.as_deref(), Some("ramp"));
+    }
+
+    /// Test profile names
+    ///
+    /// Asserts short names of letters, digits, dashes and underscores are accepted, and others refused
+    #[test]
+    fn test_profile_names() {
+        assert!(Profile::is_valid_name("ace"));
+        assert!(Profile::is_valid_name("team_member-2"));
+        assert!(!Profile::is_valid_name(""));
+        assert!(!Profile::is_valid_name("../ace"));
+        assert!(!Profile::is_valid_name("a name"));
+        assert!(!Profile::is_valid_name("a-very-long-profile-name"));
+    }
+}
@@ -0,0 +1,620 @@
+//! # Round Engine
+//!
+//! This module drives a round of blackjack as a state machine, without any terminal I/O.
+//!
+//! A front end (terminal, bot, server) reads `Game::phase()`, decides on an `Action`,
+//! and passes it to `Game::apply()`. The game advances as far as it can on its own —
+//! dealing, peeking, playing the dealer's hand, settling bets — and returns the `Event`s
+//! that happened along the way, so the front end can show them.
+
+use crate::{
+    card::Card,
+    game::{Action, Game, RoundResult},
+    rules::SurrenderRule,
+};
+use std::{error::Error, fmt};
+
+/// The phases a round moves through, in order
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    /// Waiting for `Action::Bet` to start the first round
+    Betting,
+    /// Cards are out; waiting for a decision on an `Offer` before play starts
+    Dealing,
+    /// Waiting for the player to act on the active hand
+    PlayerTurn,
+    /// Dealer is playing out their hand (never waits for input)
+    DealerTurn,
+    /// Round is over and bets are paid; waiting for `Action::Bet` to start the next one
+    Settlement,
+}
+
+/// Decisions the player is offered after the deal, before play starts
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Offer {
+    /// Surrender half the bet before the dealer checks for blackjack
+    EarlySurrender,
+    /// Take a 1:1 payout on a blackjack against a dealer ace
+    EvenMoney,
+    /// Side bet of up to half the bet that the dealer has blackjack, paying 2:1
+    Insurance,
+}
+
+/// Who a card was dealt to
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Seat {
+    /// The player's hand at this index in `Game::player_hands`
+    Player(usize),
+    Dealer,
+}
+
+/// Everything that can happen while a round is played, in the order it happened
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// The phase changed
+    Phase(Phase),
+    /// A round was started for `bet`; its first cards follow
+    RoundStarted {
+        bet: u32,
+    },
+    /// The shoe was shuffled: before this round once the cut card came out,
+    /// or mid-round, before the next card, if it ran dry
+    Shuffled,
+    /// A face-up card was dealt
+    CardDealt {
+        to: Seat,
+        card: Card,
+    },
+    /// The dealer's hole card was dealt face down
+    HoleCardDealt,
+    /// The dealer's hole card was turned face up
+    HoleCardRevealed(Card),
+    /// The player is offered a decision before play starts
+    Offered(Offer),
+    InsuranceTaken(u32),
+    EvenMoneyTaken,
+    /// The dealer checked the hole card for blackjack
+    DealerPeeked {
+        blackjack: bool,
+    },
+    /// Play moved on to the player's hand at this index
+    NextHand(usize),
+    Doubled {
+        hand: usize,
+        bet: u32,
+    },
+    Split {
+        hand: usize,
+    },
+    Surrendered {
+        hand: usize,
+    },
+    /// The insurance side bet was paid (`net` > 0) or lost
+    InsuranceSettled {
+        net: i64,
+    },
+    /// A player hand was settled, winning or losing `net` dollars
+    HandSettled {
+        hand: usize,
+        result: RoundResult,
+        bet: u32,
+        net: i64,
+    },
+    /// The round is over; `net` is the player's total win or loss
+    RoundOver {
+        net: i64,
+        bankroll: u32,
+    },
+}
+
+/// Reasons `Game::apply()` can reject an action
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameError {
+    /// The action can't be taken in the current phase
+    WrongPhase { phase: Phase, action: Action },
+    /// The bet is outside the table limits or more than the bankroll
+    InvalidBet(u32),
+    /// The action is not allowed for the current hand or offer
+    NotAllowed(Action),
+    /// A stacked shoe ran out of cards partway through the round
+    OutOfCards,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::WrongPhase { phase, action } => {
+                write!(f, "{:?} is not possible during {:?}", action, phase)
+            }
+            GameError::InvalidBet(bet) => write!(f, "a bet of ${} is not allowed", bet),
+            GameError::NotAllowed(action) => write!(f, "{:?} is not allowed right now", action),
+            GameError::OutOfCards => write!(f, "the shoe ran out of cards"),
+        }
+    }
+}
+
+impl Error for GameError {}
+
+impl Game {
+    /// Returns the phase the round is in
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Returns the decision the player is being offered, if the round is in `Phase::Dealing`
+    pub fn offer(&self) -> Option<Offer> {
+        match self.phase {
+            Phase::Dealing => self.offers.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// Apply the player's action and advance the round as far as possible
+    ///
+    /// Returns every event that happened, or an error (leaving the game untouched)
+    /// if the action isn't valid right now, or `OutOfCards` (leaving the round where
+    /// the cards ran out) if a stacked shoe is used up:
+    /// * `Betting` / `Settlement`: `Bet`
+    /// * `Dealing`: `Decline`, or accept the offer with `Surrender`, `EvenMoney` or `Insurance`
+    /// * `PlayerTurn`: any of `allowed_actions()`
+    pub fn apply(&mut self, action: Action) -> Result<Vec<Event>, GameError> {
+        let mut events = Vec::new();
+
+        match (self.phase, action) {
+            (Phase::Betting | Phase::Settlement, Action::Bet(bet)) => {
+                if !self.rules.is_valid_bet(bet, self.bankroll) {
+                    return Err(GameError::InvalidBet(bet));
+                }
+                self.start_round(bet, &mut events)?;
+            }
+            (Phase::Dealing, _) => self.apply_offer(action, &mut events)?,
+            (Phase::PlayerTurn, _) => self.apply_play(action, &mut events)?,
+            (phase, action) => return Err(GameError::WrongPhase { phase, action }),
+        }
+
+        Ok(events)
+    }
+
+    /// Deal a new round for `bet` and move to the first decision
+    fn start_round(&mut self, bet: u32, events: &mut Vec<Event>) -> Result<(), GameError> {
+        self.bet = bet;
+        if self.initial_deal()? {
+            events.push(Event::Shuffled);
+        }
+
+        events.push(Event::RoundStarted { bet });
+        events.push(self.player_card_event(0, 0));
+        events.push(self.dealer_card_event(0));
+        events.push(self.player_card_event(0, 1));
+        if self.dealer_hand.cards.len() > 1 {
+            events.push(Event::HoleCardDealt);
+        }
+
+        self.offers.clear();
+        if self.rules.surrender == SurrenderRule::Early && self.dealer_peeks() {
+            self.offers.push(Offer::EarlySurrender);
+        }
+        if self.insurance_offered() {
+            if self.player_hands[0].is_natural() {
+                self.offers.push(Offer::EvenMoney);
+            } else if self.max_insurance() > 0 {
+                self.offers.push(Offer::Insurance);
+            }
+        }
+
+        self.next_offer(events)
+    }
+
+    /// Handle a decision on the current offer
+    fn apply_offer(&mut self, action: Action, events: &mut Vec<Event>) -> Result<(), GameError> {
+        let offer = self.offers[0];
+
+        match (offer, action) {
+            (_, Action::Decline) => {}
+            (Offer::EarlySurrender, Action::Surrender) => {
+                self.surrender();
+                events.push(Event::Surrendered { hand: 0 });
+                return self.finish_round(events);
+            }
+            (Offer::EvenMoney, Action::EvenMoney) => {
+                self.take_even_money();
+                events.push(Event::EvenMoneyTaken);
+                return self.finish_round(events);
+            }
+            (Offer::Insurance, Action::Insurance(amount))
+                if amount > 0 && amount <= self.max_insurance() =>
+            {
+                self.take_insurance(amount);
+                events.push(Event::InsuranceTaken(amount));
+            }
+            _ => return Err(GameError::NotAllowed(action)),
+        }
+
+        self.offers.remove(0);
+        self.next_offer(events)
+    }
+
+    /// Present the next offer, or once there are none left, peek and start the player's turn
+    fn next_offer(&mut self, events: &mut Vec<Event>) -> Result<(), GameError> {
+        if let Some(offer) = self.offers.first() {
+            if self.phase != Phase::Dealing {
+                self.phase = Phase::Dealing;
+                events.push(Event::Phase(Phase::Dealing));
+            }
+            events.push(Event::Offered(*offer));
+            return Ok(());
+        }
+
+        if self.dealer_peeks() {
+            let blackjack = self.peek_for_blackjack();
+            events.push(Event::DealerPeeked { blackjack });
+
+            if blackjack {
+                events.push(Event::HoleCardRevealed(self.dealer_hand.cards[1]));
+                return self.finish_round(events);
+            }
+        }
+
+        if self.player_hand().is_blackjack() {
+            return self.finish_round(events);
+        }
+
+        self.phase = Phase::PlayerTurn;
+        events.push(Event::Phase(Phase::PlayerTurn));
+        Ok(())
+    }
+
+    /// Handle a decision on the active hand
+    fn apply_play(&mut self, action: Action, events: &mut Vec<Event>) -> Result<(), GameError> {
+        if !self.allowed_actions().contains(&action) {
+            return Err(GameError::NotAllowed(action));
+        }
+        let hand = self.active_hand;
+
+        match action {
+            Action::Hit => {
+                self.deal_to_player()?;
+                let last = self.player_hand().cards.len() - 1;
+                self.push_dealt(events, self.player_card_event(hand, last));
+            }
+            Action::Double => {
+                self.double_down()?;
+                let bet = self.player_hands[hand].bet;
+                events.push(Event::Doubled { hand, bet });
+                self.push_dealt(events, self.player_card_event(hand, 2));
+            }
+            Action::Split => {
+                self.split()?;
+                events.push(Event::Split { hand });
+                self.push_dealt(events, self.player_card_event(hand, 1));
+            }
+            Action::Surrender => {
+                self.surrender();
+                events.push(Event::Surrendered { hand });
+            }
+            _ => {}
+        }
+
+        if action == Action::Stand || self.hand_finished() {
+            self.next_player_hand(events)?;
+        }
+
+        Ok(())
+    }
+
+    /// Move on to the next unfinished hand, or to the dealer's turn once every hand is played
+    fn next_player_hand(&mut self, events: &mut Vec<Event>) -> Result<(), GameError> {
+        loop {
+            if !self.next_hand()? {
+                return self.dealer_turn(events);
+            }
+
+            let hand = self.active_hand;
+            events.push(Event::NextHand(hand));
+            self.push_dealt(events, self.player_card_event(hand, 1));
+
+            if !self.hand_finished() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Play out the dealer's hand, then settle the round
+    fn dealer_turn(&mut self, events: &mut Vec<Event>) -> Result<(), GameError> {
+        if !self.dealer_must_play() {
+            return self.finish_round(events);
+        }
+
+        self.phase = Phase::DealerTurn;
+        events.push(Event::Phase(Phase::DealerTurn));
+        self.reveal(events)?;
+
+        while !self.dealer_hand.is_blackjack() && self.dealer_should_hit() {
+            self.deal_to_dealer()?;
+            self.push_dealt(
+                events,
+                self.dealer_card_event(self.dealer_hand.cards.len() - 1),
+            );
+        }
+
+        self.finish_round(events)
+    }
+
+    /// Turn over the hole card, or deal the dealer's second card at a no-hole-card table
+    fn reveal(&mut self, events: &mut Vec<Event>) -> Result<(), GameError> {
+        if self.hole_revealed {
+            return Ok(());
+        }
+
+        let had_hole_card = self.dealer_hand.cards.len() > 1;
+        self.reveal_hole_card()?;
+
+        if had_hole_card {
+            events.push(Event::HoleCardRevealed(self.dealer_hand.cards[1]));
+        } else {
+            self.push_dealt(events, self.dealer_card_event(1));
+        }
+
+        Ok(())
+    }
+
+    /// Reveal the hole card if it matters, settle every bet, and move to `Phase::Settlement`
+    ///
+    /// At a no-hole-card table the dealer only draws a second card if a blackjack
+    /// would change the outcome
+    fn finish_round(&mut self, events: &mut Vec<Event>) -> Result<(), GameError> {
+        let dealer_card_matters = self.rules.hole_card
+            || self.insurance > 0
+            || self.player_hands[0].is_natural()
+            || self.player_hands[0].is_surrendered;
+
+        if dealer_card_matters {
+            self.reveal(events)?;
+        }
+
+        self.phase = Phase::Settlement;
+        events.push(Event::Phase(Phase::Settlement));
+
+        let mut round_net: i64 = 0;
+
+        if self.insurance > 0 {
+            let net = if self.insurance_won() {
+                2 * self.insurance as i64
+            } else {
+                -(self.insurance as i64)
+            };
+            round_net += net;
+            events.push(Event::InsuranceSettled { net });
+        }
+
+        for (hand, result) in self.determine_winner().into_iter().enumerate() {
+            let bet = self.player_hands[hand].bet;
+            let net = self.hand_net(result, bet);
+
+            round_net += net;
+            events.push(Event::HandSettled {
+                hand,
+                result,
+                bet,
+                net,
+            });
+        }
+
+        self.bankroll = (self.bankroll as i64 + round_net).clamp(0, u32::MAX as i64) as u32;
+        self.i = self.i.saturating_add(1);
+        events.push(Event::RoundOver {
+            net: round_net,
+            bankroll: self.bankroll,
+        });
+
+        Ok(())
+    }
+
+    /// Dollars won (positive) or lost (negative) on a hand with the given result and bet
+    pub fn hand_net(&self, result: RoundResult, bet: u32) -> i64 {
+        match result {
+            RoundResult::PlayerWin => bet as i64,
+            RoundResult::PlayerBlackjack => self.rules.blackjack_payout.pay(bet) as i64,
+            RoundResult::DealerWin => -(bet as i64),
+            RoundResult::Push => 0,
+            RoundResult::Surrender => -((bet - self.surrender_refund(bet)) as i64),
+        }
+    }
+
+    /// Push the event of a card just dealt, after `Shuffled` if the shoe ran dry and was reshuffled for it
+    fn push_dealt(&mut self, events: &mut Vec<Event>, event: Event) {
+        if std::mem::take(&mut self.reshuffled) {
+            events.push(Event::Shuffled);
+        }
+        events.push(event);
+    }
+
+    /// Event for the card at `index` in the player's hand at `hand`
+    fn player_card_event(&self, hand: usize, index: usize) -> Event {
+        Event::CardDealt {
+            to: Seat::Player(hand),
+            card: self.player_hands[hand].hand.cards[index],
+        }
+    }
+
+    /// Event for the card at `index` in the dealer's hand
+    fn dealer_card_event(&self, index: usize) -> Event {
+        Event::CardDealt {
+            to: Seat::Dealer,
+            card: self.dealer_hand.cards[index],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{card::Card, rules::TableRules, strategy::MimicDealer};
+
+    /// Test a full round driven through `apply()`
+    ///
+    /// Bets, hits, stands, and asserts the dealer plays out and the bankroll is settled.
+    /// Without an offer, the round goes straight to the player's turn without a dealing phase
+    #[test]
+    fn test_round_state_machine() {
+        let mut game = Game::scripted(TableRules::default(), "TH 6S 2C TD 5H 5C");
+        assert_eq!(game.phase(), Phase::Betting);
+
+        let events = game.apply(Action::Bet(100)).unwrap();
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+        assert_eq!(events[0], Event::RoundStarted { bet: 100 });
+        assert!(!events.contains(&Event::Phase(Phase::Dealing)));
+
+        game.apply(Action::Hit).unwrap();
+        assert_eq!(game.player_hand().value(), 17);
+
+        let events = game.apply(Action::Stand).unwrap();
+        assert_eq!(game.phase(), Phase::Settlement);
+        assert!(events.contains(&Event::Phase(Phase::DealerTurn)));
+        assert!(events.contains(&Event::CardDealt {
+            to: Seat::Dealer,
+            card: "5C".parse().unwrap()
+        }));
+        assert_eq!(
+            events.last(),
+            Some(&Event::RoundOver {
+                net: -100,
+                bankroll: 900
+            })
+        );
+        assert_eq!(game.i, 1);
+    }
+
+    /// Test actions rejected by the state machine
+    ///
+    /// Asserts playing before betting, betting over the bankroll, and an unavailable
+    /// split are all rejected without changing the phase
+    #[test]
+    fn test_invalid_actions() {
+        let mut game = Game::scripted(TableRules::default(), "TH 6S 7C TD");
+
+        assert!(matches!(
+            game.apply(Action::Hit),
+            Err(GameError::WrongPhase { .. })
+        ));
+        assert_eq!(
+            game.apply(Action::Bet(5000)),
+            Err(GameError::InvalidBet(5000))
+        );
+
+        game.apply(Action::Bet(100)).unwrap();
+        assert_eq!(
+            game.apply(Action::Split),
+            Err(GameError::NotAllowed(Action::Split))
+        );
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+    }
+
+    /// Test insurance offer and dealer peek
+    ///
+    /// Dealer shows an ace, player takes insurance, and the dealer peeks a blackjack;
+    /// asserts the round ends with the insurance paying for the lost hand
+    #[test]
+    fn test_insurance_offer() {
+        let mut game = Game::scripted(TableRules::default(), "TH AS 9C KD");
+
+        let events = game.apply(Action::Bet(100)).unwrap();
+        assert_eq!(game.phase(), Phase::Dealing);
+        assert_eq!(game.offer(), Some(Offer::Insurance));
+        assert!(events.contains(&Event::HoleCardDealt));
+        assert_eq!(
+            events[events.len() - 2..],
+            [
+                Event::Phase(Phase::Dealing),
+                Event::Offered(Offer::Insurance)
+            ]
+        );
+
+        let events = game.apply(Action::Insurance(50)).unwrap();
+        assert!(events.contains(&Event::DealerPeeked { blackjack: true }));
+        assert!(events.contains(&Event::InsuranceSettled { net: 100 }));
+        assert_eq!(game.phase(), Phase::Settlement);
+        assert_eq!(game.bankroll, 1000);
+    }
+
+    /// Test split hands through the state machine
+    ///
+    /// Splits eights, stands on both hands, and asserts each hand is settled separately
+    #[test]
+    fn test_split_round() {
+        let mut game = Game::scripted(TableRules::default(), "8H 6S 8C TD TH 9C 7S");
+
+        game.apply(Action::Bet(100)).unwrap();
+        game.apply(Action::Split).unwrap();
+
+        let events = game.apply(Action::Stand).unwrap();
+        assert!(events.contains(&Event::NextHand(1)));
+        assert_eq!(game.phase(), Phase::PlayerTurn);
+
+        let events = game.apply(Action::Stand).unwrap();
+        let settled: Vec<RoundResult> = events
+            .iter()
+            .filter_map(|e| match e {
+                Event::HandSettled { result, .. } => Some(*result),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            settled,
+            vec![RoundResult::PlayerWin, RoundResult::PlayerWin]
+        );
+        assert_eq!(game.bankroll, 1200);
+    }
+
+    /// Test a shoe running dry mid-round
+    ///
+    /// Deals a single deck down to its last card and asserts that when it runs out mid-round,
+    /// `Shuffled` comes before the next card and no card on the table is dealt twice
+    #[test]
+    fn test_shoe_runs_dry() {
+        let rules = TableRules {
+            penetration: 1.0,
+            ..TableRules::default()
+        };
+        let mut game = Game::with_seed(100_000, rules, 4);
+        let mut mid_round = 0;
+
+        for _ in 0..100 {
+            let events = game.play_round(&mut MimicDealer).unwrap();
+            let mut seen: Vec<Card> = Vec::new();
+
+            for (i, event) in events.iter().enumerate() {
+                match event {
+                    Event::Shuffled if i > 0 => mid_round += 1,
+                    Event::CardDealt { card, .. } | Event::HoleCardRevealed(card) => {
+                        assert!(!seen.contains(card), "{} dealt twice in a round", card);
+                        seen.push(*card);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        assert!(mid_round > 0);
+    }
+
+    /// Test a stacked shoe running out
+    ///
+    /// Stacks too few cards for a round and asserts `apply()` and `play_round()` return
+    /// `OutOfCards` instead of panicking
+    #[test]
+    fn test_out_of_cards() {
+        let mut game = Game::scripted(TableRules::vegas_strip(), "TH 7C");
+        assert_eq!(game.apply(Action::Bet(10)), Err(GameError::OutOfCards));
+
+        let mut game = Game::scripted(TableRules::vegas_strip(), "TH 7C 6D 9S");
+        assert_eq!(
+            game.play_round(&mut MimicDealer),
+            Err(GameError::OutOfCards)
+        );
+    }
+}
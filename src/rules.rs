@@ -0,0 +1,334 @@
+//! # Table Rules
+//!
+//! This module contains the `TableRules` struct, which collects every rule
+//! that varies from casino to casino, along with a few well-known presets.
+//!
+//! `Game` consults these rules for every decision instead of hardcoding them.
+
+use std::fmt;
+
+/// How much a player blackjack pays, relative to the bet
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BlackjackPayout {
+    ThreeToTwo,
+    SixToFive,
+    EvenMoney,
+}
+
+impl BlackjackPayout {
+    /// Returns the winnings for a blackjack on the given bet (rounded down to whole dollars)
+    ///
+    /// Winnings too big for a `u32`, on bets near the default unlimited `max_bet`, are capped at `u32::MAX`
+    pub fn pay(&self, bet: u32) -> u32 {
+        let bet = bet as u64;
+        let winnings = match self {
+            BlackjackPayout::ThreeToTwo => (bet * 3) / 2,
+            BlackjackPayout::SixToFive => (bet * 6) / 5,
+            BlackjackPayout::EvenMoney => bet,
+        };
+        winnings.min(u32::MAX as u64) as u32
+    }
+}
+
+impl fmt::Display for BlackjackPayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ratio = match self {
+            BlackjackPayout::ThreeToTwo => "3:2",
+            BlackjackPayout::SixToFive => "6:5",
+            BlackjackPayout::EvenMoney => "1:1",
+        };
+        write!(f, "{}", ratio)
+    }
+}
+
+/// Which starting hands the player may double down on
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DoubleRule {
+    AnyTwoCards,
+    NineToEleven,
+    TenToEleven,
+}
+
+impl DoubleRule {
+    /// Check if a starting hand worth `total` may be doubled
+    pub fn allows(&self, total: u8) -> bool {
+        match self {
+            DoubleRule::AnyTwoCards => true,
+            DoubleRule::NineToEleven => (9..=11).contains(&total),
+            DoubleRule::TenToEleven => (10..=11).contains(&total),
+        }
+    }
+}
+
+/// Whether, and when, the player may surrender half their bet
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SurrenderRule {
+    NotAllowed,
+    Late,
+    Early,
+}
+
+/// How half of an odd bet is rounded when refunding a surrender
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SurrenderRounding {
+    /// The house keeps the odd chip: $25 surrendered refunds $12
+    Down,
+    /// The player keeps the odd chip: $25 surrendered refunds $13
+    Up,
+}
+
+impl SurrenderRounding {
+    /// Returns the amount refunded when surrendering the given bet
+    pub fn refund(&self, bet: u32) -> u32 {
+        match self {
+            SurrenderRounding::Down => bet / 2,
+            SurrenderRounding::Up => bet.div_ceil(2),
+        }
+    }
+}
+
+/// Every configurable rule of a blackjack table
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TableRules {
+    /// Dealer draws on soft 17 (H17) instead of standing (S17)
+    pub dealer_hits_soft_17: bool,
+    /// Dealer takes a face-down hole card and peeks for blackjack under a ten or ace (American).
+    /// When false, the dealer's second card is dealt after the player acts (European no-hole-card)
+    pub hole_card: bool,
+    pub blackjack_payout: BlackjackPayout,
+    /// Number of 52-card decks shuffled together into the shoe
+    pub decks: u8,
+    /// Fraction of the shoe dealt before the cut card comes out (0.0 - 1.0)
+    pub penetration: f32,
+    pub double: DoubleRule,
+    pub double_after_split: bool,
+    /// Maximum number of hands a player can hold after splitting
+    pub max_split_hands: u8,
+    pub resplit_aces: bool,
+    /// When false, split aces receive exactly one card each
+    pub hit_split_aces: bool,
+    pub surrender: SurrenderRule,
+    pub surrender_rounding: SurrenderRounding,
+    pub min_bet: u32,
+    pub max_bet: u32,
+}
+
+impl TableRules {
+    /// Preset: Las Vegas Strip
+    ///
+    /// 4 decks, dealer stands on soft 17, double any two cards (also after split),
+    /// split to 4 hands, no surrender
+    pub fn vegas_strip() -> Self {
+        TableRules {
+            dealer_hits_soft_17: false,
+            hole_card: true,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+            decks: 4,
+            penetration: 0.75,
+            double: DoubleRule::AnyTwoCards,
+            double_after_split: true,
+            max_split_hands: 4,
+            resplit_aces: false,
+            hit_split_aces: false,
+            surrender: SurrenderRule::NotAllowed,
+            surrender_rounding: SurrenderRounding::Down,
+            min_bet: 10,
+            max_bet: 5000,
+        }
+    }
+
+    /// Preset: Atlantic City
+    ///
+    /// 8 decks, dealer stands on soft 17, double any two cards (also after split),
+    /// split to 4 hands, late surrender
+    pub fn atlantic_city() -> Self {
+        TableRules {
+            dealer_hits_soft_17: false,
+            hole_card: true,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+            decks: 8,
+            penetration: 0.75,
+            double: DoubleRule::AnyTwoCards,
+            double_after_split: true,
+            max_split_hands: 4,
+            resplit_aces: false,
+            hit_split_aces: false,
+            surrender: SurrenderRule::Late,
+            surrender_rounding: SurrenderRounding::Down,
+            min_bet: 10,
+            max_bet: 5000,
+        }
+    }
+
+    /// Preset: European no-hole-card
+    ///
+    /// 6 decks, dealer stands on soft 17 and takes no hole card, double on 9-11 only,
+    /// no resplitting, no surrender
+    pub fn european() -> Self {
+        TableRules {
+            dealer_hits_soft_17: false,
+            hole_card: false,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+            decks: 6,
+            penetration: 0.75,
+            double: DoubleRule::NineToEleven,
+            double_after_split: true,
+            max_split_hands: 2,
+            resplit_aces: false,
+            hit_split_aces: false,
+            surrender: SurrenderRule::NotAllowed,
+            surrender_rounding: SurrenderRounding::Down,
+            min_bet: 5,
+            max_bet: 2500,
+        }
+    }
+
+    /// Looks up a preset by name, e.g. `"vegas"`, `"atlantic-city"` or `"european"`
+    ///
+    /// Returns `None` if no preset matches
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().replace(['_', ' '], "-").as_str() {
+            "default" | "single-deck" => Some(TableRules::default()),
+            "vegas" | "vegas-strip" => Some(TableRules::vegas_strip()),
+            "atlantic" | "atlantic-city" => Some(TableRules::atlantic_city()),
+            "european" | "european-no-hole-card" | "enhc" => Some(TableRules::european()),
+            _ => None,
+        }
+    }
+
+    /// Returns the names accepted by `preset()`, one per preset
+    pub fn preset_names() -> &'static [&'static str] {
+        &["default", "vegas-strip", "atlantic-city", "european"]
+    }
+
+    /// Check if a bet is allowed at this table for the given bankroll
+    pub fn is_valid_bet(&self, bet: u32, bankroll: u32) -> bool {
+        bet >= self.min_bet && bet <= self.max_bet && bet <= bankroll
+    }
+}
+
+/// The classic single-deck game this CLI has always played
+impl Default for TableRules {
+    fn default() -> Self {
+        TableRules {
+            dealer_hits_soft_17: false,
+            hole_card: true,
+            blackjack_payout: BlackjackPayout::ThreeToTwo,
+            decks: 1,
+            penetration: 0.6,
+            double: DoubleRule::AnyTwoCards,
+            double_after_split: true,
+            max_split_hands: 4,
+            resplit_aces: false,
+            hit_split_aces: false,
+            surrender: SurrenderRule::NotAllowed,
+            surrender_rounding: SurrenderRounding::Down,
+            min_bet: 1,
+            max_bet: u32::MAX,
+        }
+    }
+}
+
+/// Short one-line summary, e.g. `6 decks, S17, BJ pays 3:2, DAS, no surrender`
+impl fmt::Display for TableRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let decks = if self.decks == 1 { "deck" } else { "decks" };
+        let soft_17 = if self.dealer_hits_soft_17 {
+            "H17"
+        } else {
+            "S17"
+        };
+        write!(
+            f,
+            "{} {}, {}, BJ pays {}",
+            self.decks, decks, soft_17, self.blackjack_payout
+        )?;
+
+        match self.double {
+            DoubleRule::AnyTwoCards => {}
+            DoubleRule::NineToEleven => write!(f, ", double 9-11")?,
+            DoubleRule::TenToEleven => write!(f, ", double 10-11")?,
+        }
+        if self.double_after_split {
+            write!(f, ", DAS")?;
+        }
+        if !self.hole_card {
+            write!(f, ", no hole card")?;
+        }
+
+        let surrender = match self.surrender {
+            SurrenderRule::NotAllowed => "no surrender",
+            SurrenderRule::Late => "late surrender",
+            SurrenderRule::Early => "early surrender",
+        };
+        write!(f, ", {}", surrender)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test `BlackjackPayout::pay()`
+    ///
+    /// Asserts each payout ratio on a $100 bet, that odd amounts round down, and that
+    /// bets too big to multiply in a `u32` are paid without overflowing
+    #[test]
+    fn test_blackjack_payout() {
+        assert_eq!(BlackjackPayout::ThreeToTwo.pay(100), 150);
+        assert_eq!(BlackjackPayout::SixToFive.pay(100), 120);
+        assert_eq!(BlackjackPayout::EvenMoney.pay(100), 100);
+
+        assert_eq!(BlackjackPayout::ThreeToTwo.pay(5), 7);
+
+        assert_eq!(BlackjackPayout::SixToFive.pay(2_000_000_000), 2_400_000_000);
+        assert_eq!(BlackjackPayout::ThreeToTwo.pay(u32::MAX), u32::MAX);
+    }
+
+    /// Test `SurrenderRounding::refund()`
+    ///
+    /// Asserts even bets refund exactly half, and odd bets round toward the house or the player
+    #[test]
+    fn test_surrender_refund() {
+        assert_eq!(SurrenderRounding::Down.refund(100), 50);
+        assert_eq!(SurrenderRounding::Up.refund(100), 50);
+
+        assert_eq!(SurrenderRounding::Down.refund(25), 12);
+        assert_eq!(SurrenderRounding::Up.refund(25), 13);
+    }
+
+    /// Test `preset()` lookup
+    ///
+    /// Asserts every advertised preset name resolves, aliases match, and unknown names are rejected
+    #[test]
+    fn test_presets() {
+        for name in TableRules::preset_names() {
+            assert!(TableRules::preset(name).is_some());
+        }
+
+        assert_eq!(
+            TableRules::preset("Atlantic City"),
+            Some(TableRules::atlantic_city())
+        );
+        assert_eq!(TableRules::preset("enhc"), Some(TableRules::european()));
+        assert_eq!(TableRules::preset("macau"), None);
+    }
+
+    /// Test `is_valid_bet()`
+    ///
+    /// Asserts the table minimum, table maximum and bankroll are all enforced
+    #[test]
+    fn test_valid_bet() {
+        let rules = TableRules::vegas_strip();
+
+        assert!(rules.is_valid_bet(10, 1000));
+        assert!(!rules.is_valid_bet(5, 1000));
+        assert!(!rules.is_valid_bet(6000, 10000));
+        assert!(!rules.is_valid_bet(500, 100));
+    }
+}
@@ -0,0 +1,426 @@
+//! # Save
+//!
+//! Saving a session between rounds and loading it back: the bankroll, the round counter,
+//! the table rules and the shoe, down to the order of the cards left in it, and the
+//! session's statistics so far.
+//!
+//! The format is plain text, one `key=value` per line under a `version` line. Loading
+//! ignores keys it doesn't know and falls back to defaults for keys that are missing,
+//! so fields can be added in later versions without breaking older saves.
+
+use std::{collections::HashMap, error::Error, fmt, str::FromStr};
+
+use crate::{
+    card::parse_cards,
+    game::Game,
+    rules::{BlackjackPayout, DoubleRule, SurrenderRounding, SurrenderRule, TableRules},
+    shoe::Shoe,
+    stats::SessionStats,
+};
+
+/// Version written to new saves; saves from later versions are refused
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Reasons a save can't be loaded
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    /// The save was written by a newer version of the format
+    UnsupportedVersion(u32),
+    /// A required key is missing
+    Missing(&'static str),
+    /// A key holds a value that can't be read
+    Invalid { key: String, value: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::UnsupportedVersion(version) => write!(
+                f,
+                "save format version {} is newer than this version supports ({})",
+                version, FORMAT_VERSION
+            ),
+            LoadError::Missing(key) => write!(f, "save is missing '{}'", key),
+            LoadError::Invalid { key, value } => {
+                write!(f, "save has an invalid value '{}' for '{}'", value, key)
+            }
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+/// A value that can be written to and read from a save
+trait SaveValue: Sized {
+    fn to_value(&self) -> String;
+    fn from_value(value: &str) -> Option<Self>;
+}
+
+macro_rules! save_value_from_str {
+    ($($t:ty),*) => {
+        $(impl SaveValue for $t {
+            fn to_value(&self) -> String {
+                self.to_string()
+            }
+
+            fn from_value(value: &str) -> Option<Self> {
+                <$t as FromStr>::from_str(value).ok()
+            }
+        })*
+    };
+}
+
+save_value_from_str!(bool, u8, u16, u32, u64, i64, f32);
+
+impl SaveValue for BlackjackPayout {
+    fn to_value(&self) -> String {
+        self.to_string()
+    }
+
+    fn from_value(value: &str) -> Option<Self> {
+        match value {
+            "3:2" => Some(BlackjackPayout::ThreeToTwo),
+            "6:5" => Some(BlackjackPayout::SixToFive),
+            "1:1" => Some(BlackjackPayout::EvenMoney),
+            _ => None,
+        }
+    }
+}
+
+impl SaveValue for DoubleRule {
+    fn to_value(&self) -> String {
+        match self {
+            DoubleRule::AnyTwoCards => "any",
+            DoubleRule::NineToEleven => "9-11",
+            DoubleRule::TenToEleven => "10-11",
+        }
+        .to_string()
+    }
+
+    fn from_value(value: &str) -> Option<Self> {
+        match value {
+            "any" => Some(DoubleRule::AnyTwoCards),
+            "9-11" => Some(DoubleRule::NineToEleven),
+            "10-11" => Some(DoubleRule::TenToEleven),
+            _ => None,
+        }
+    }
+}
+
+impl SaveValue for SurrenderRule {
+    fn to_value(&self) -> String {
+        match self {
+            SurrenderRule::NotAllowed => "none",
+            SurrenderRule::Late => "late",
+            SurrenderRule::Early => "early",
+        }
+        .to_string()
+    }
+
+    fn from_value(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(SurrenderRule::NotAllowed),
+            "late" => Some(SurrenderRule::Late),
+            "early" => Some(SurrenderRule::Early),
+            _ => None,
+        }
+    }
+}
+
+impl SaveValue for SurrenderRounding {
+    fn to_value(&self) -> String {
+        match self {
+            SurrenderRounding::Down => "down",
+            SurrenderRounding::Up => "up",
+        }
+        .to_string()
+    }
+
+    fn from_value(value: &str) -> Option<Self> {
+        match value {
+            "down" => Some(SurrenderRounding::Down),
+            "up" => Some(SurrenderRounding::Up),
+            _ => None,
+        }
+    }
+}
+
+/// The `key=value` pairs of a save
+struct Fields<'a>(HashMap<&'a str, &'a str>);
+
+impl<'a> Fields<'a> {
+    /// Read the pairs, skipping blank lines and `#` comments
+    fn parse(text: &'a str) -> Self {
+        Fields(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .filter_map(|line| line.split_once('='))
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .collect(),
+        )
+    }
+
+    /// The value of `key`, `None` if it is missing
+    fn get<T: SaveValue>(&self, key: &str) -> Result<Option<T>, LoadError> {
+        match self.0.get(key) {
+            None => Ok(None),
+            Some(value) => T::from_value(value).map(Some).ok_or(LoadError::Invalid {
+                key: key.to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+
+    /// Overwrite `field` with the value of `key`, if there is one
+    fn read<T: SaveValue>(&self, key: &str, field: &mut T) -> Result<(), LoadError> {
+        if let Some(value) = self.get(key)? {
+            *field = value;
+        }
+        Ok(())
+    }
+}
+
+impl Game {
+    /// Write the session to the save format
+    ///
+    /// Meant for the time between rounds: the cards of a round in progress are not kept
+    pub fn to_save(&self) -> String {
+        let rules = &self.rules;
+        let shoe = self.shoe();
+        let cards = shoe
+            .cards()
+            .iter()
+            .map(|card| card.code())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let fields: [(&str, String); 20] = [
+            ("version", FORMAT_VERSION.to_value()),
+            ("bankroll", self.bankroll.to_value()),
+            ("round", self.i.to_value()),
+            (
+                "rules.dealer_hits_soft_17",
+                rules.dealer_hits_soft_17.to_value(),
+            ),
+            ("rules.hole_card", rules.hole_card.to_value()),
+            ("rules.blackjack_payout", rules.blackjack_payout.to_value()),
+            ("rules.decks", rules.decks.to_value()),
+            ("rules.penetration", rules.penetration.to_value()),
+            ("rules.double", rules.double.to_value()),
+            (
+                "rules.double_after_split",
+                rules.double_after_split.to_value(),
+            ),
+            ("rules.max_split_hands", rules.max_split_hands.to_value()),
+            ("rules.resplit_aces", rules.resplit_aces.to_value()),
+            ("rules.hit_split_aces", rules.hit_split_aces.to_value()),
+            ("rules.surrender", rules.surrender.to_value()),
+            (
+                "rules.surrender_rounding",
+                rules.surrender_rounding.to_value(),
+            ),
+            ("rules.min_bet", rules.min_bet.to_value()),
+            ("rules.max_bet", rules.max_bet.to_value()),
+            ("shoe.seed", shoe.seed().to_value()),
+            ("shoe.shuffles", shoe.shuffles().to_value()),
+            ("shoe.cards", cards),
+        ];
+
+        let mut save = String::from("# rust-blackjack-cli saved session\n");
+        for (key, value) in fields {
+            save.push_str(&format!("{}={}\n", key, value));
+        }
+        save
+    }
+
+    /// Load a session written by `to_save()`, ready for the next round
+    ///
+    /// Unknown keys are ignored, and missing rules fall back to `TableRules::default()`.
+    /// Without a saved shoe, a fresh one is shuffled from the saved seed (or a random one)
+    pub fn from_save(text: &str) -> Result<Game, LoadError> {
+        let fields = Fields::parse(text);
+
+        let version: u32 = fields
+            .get("version")?
+            .ok_or(LoadError::Missing("version"))?;
+        if version > FORMAT_VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+        let bankroll = fields
+            .get("bankroll")?
+            .ok_or(LoadError::Missing("bankroll"))?;
+
+        let mut rules = TableRules::default();
+        fields.read("rules.dealer_hits_soft_17", &mut rules.dealer_hits_soft_17)?;
+        fields.read("rules.hole_card", &mut rules.hole_card)?;
+        fields.read("rules.blackjack_payout", &mut rules.blackjack_payout)?;
+        fields.read("rules.decks", &mut rules.decks)?;
+        fields.read("rules.penetration", &mut rules.penetration)?;
+        fields.read("rules.double", &mut rules.double)?;
+        fields.read("rules.double_after_split", &mut rules.double_after_split)?;
+        fields.read("rules.max_split_hands", &mut rules.max_split_hands)?;
+        fields.read("rules.resplit_aces", &mut rules.resplit_aces)?;
+        fields.read("rules.hit_split_aces", &mut rules.hit_split_aces)?;
+        fields.read("rules.surrender", &mut rules.surrender)?;
+        fields.read("rules.surrender_rounding", &mut rules.surrender_rounding)?;
+        fields.read("rules.min_bet", &mut rules.min_bet)?;
+        fields.read("rules.max_bet", &mut rules.max_bet)?;
+
+        let seed = fields.get("shoe.seed")?.unwrap_or_else(rand::random);
+        let shuffles = fields.get("shoe.shuffles")?.unwrap_or(1);
+        let shoe = match fields.0.get("shoe.cards") {
+            Some(codes) => {
+                let cards = parse_cards(codes).map_err(|_| LoadError::Invalid {
+                    key: "shoe.cards".to_string(),
+                    value: codes.to_string(),
+                })?;
+                Shoe::restore(rules.decks, rules.penetration, seed, shuffles, cards)
+            }
+            None => Shoe::with_seed(rules.decks, rules.penetration, seed),
+        };
+
+        let mut game = Game::with_shoe(bankroll, rules, shoe);
+        fields.read("round", &mut game.i)?;
+
+        Ok(game)
+    }
+}
+
+impl SessionStats {
+    /// Write the statistics as `stats.*` lines, to keep with a saved session or a profile
+    pub fn to_save(&self) -> String {
+        let mut stats = self.clone();
+        let mut save = String::new();
+
+        for (key, value) in stat_fields(&mut stats) {
+            save.push_str(&format!("stats.{}={}\n", key, value));
+        }
+        save.push_str(&format!("stats.net={}\n", self.net));
+        save.push_str(&format!("stats.streak={}\n", self.streak));
+        save
+    }
+
+    /// Read the `stats.*` lines written by `to_save()`; missing ones start from zero
+    pub fn from_save(text: &str) -> Result<SessionStats, LoadError> {
+        let fields = Fields::parse(text);
+        let mut stats = SessionStats::default();
+
+        for (key, value) in stat_fields(&mut stats) {
+            fields.read(&format!("stats.{}", key), value)?;
+        }
+        fields.read("stats.net", &mut stats.net)?;
+        fields.read("stats.streak", &mut stats.streak)?;
+        Ok(stats)
+    }
+}
+
+/// The counters of `stats` by key, for reading and writing them
+fn stat_fields(stats: &mut SessionStats) -> [(&'static str, &mut u32); 14] {
+    [
+        ("rounds", &mut stats.rounds),
+        ("hands", &mut stats.hands),
+        ("wins", &mut stats.wins),
+        ("losses", &mut stats.losses),
+        ("pushes", &mut stats.pushes),
+        ("blackjacks", &mut stats.blackjacks),
+        ("surrenders", &mut stats.surrenders),
+        ("busts", &mut stats.busts),
+        ("doubles", &mut stats.doubles),
+        ("splits", &mut stats.splits),
+        ("biggest_win", &mut stats.biggest_win),
+        ("biggest_loss", &mut stats.biggest_loss),
+        ("longest_win_streak", &mut stats.longest_win_streak),
+        ("longest_loss_streak", &mut stats.longest_loss_streak),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::MimicDealer;
+
+    /// Test saving and loading a session
+    ///
+    /// Plays a few rounds, saves, loads the save, and asserts the loaded game
+    /// matches and plays on exactly like the original
+    #[test]
+    fn test_save_round_trip() {
+        let mut game = Game::with_seed(1000, TableRules::european(), 21);
+        for _ in 0..30 {
+            game.play_round(&mut MimicDealer).unwrap();
+        }
+
+        let mut loaded = Game::from_save(&game.to_save()).unwrap();
+        assert_eq!(loaded.bankroll, game.bankroll);
+        assert_eq!(loaded.i, 30);
+        assert_eq!(loaded.rules, game.rules);
+        assert_eq!(loaded.shoe(), game.shoe());
+
+        for _ in 0..30 {
+            assert_eq!(
+                loaded.play_round(&mut MimicDealer),
+                game.play_round(&mut MimicDealer)
+            );
+        }
+    }
+
+    /// Test saving the session's statistics
+    ///
+    /// Plays a few rounds, saves the statistics with the session, and asserts they load back
+    /// the same, and that a save without them starts from zero
+    #[test]
+    fn test_save_stats() {
+        let mut game = Game::with_seed(1000, TableRules::vegas_strip(), 8);
+        let mut stats = SessionStats::default();
+        for _ in 0..20 {
+            let events = game.play_round(&mut MimicDealer).unwrap();
+            stats.record(&game, &events);
+        }
+
+        let save = game.to_save() + &stats.to_save();
+        assert_eq!(SessionStats::from_save(&save), Ok(stats));
+        assert_eq!(Game::from_save(&save).unwrap().i, 20);
+
+        assert_eq!(
+            SessionStats::from_save(&game.to_save()),
+            Ok(SessionStats::default())
+        );
+        assert_eq!(
+            SessionStats::from_save("stats.wins=many\n"),
+            Err(LoadError::Invalid {
+                key: "stats.wins".to_string(),
+                value: "many".to_string()
+            })
+        );
+    }
+
+    /// Test loading saves from other versions
+    ///
+    /// Loads a minimal save with an unknown key and asserts defaults fill in the rest,
+    /// then asserts a newer version and a bad value are refused
+    #[test]
+    fn test_load_versions() {
+        let game = Game::from_save("version=1\nbankroll=250\nfuture.key=42\n").unwrap();
+        assert_eq!(game.bankroll, 250);
+        assert_eq!(game.i, 0);
+        assert_eq!(game.rules, TableRules::default());
+        assert_eq!(game.shoe().remaining(), 52);
+
+        assert_eq!(
+            Game::from_save("version=2\nbankroll=250\n"),
+            Err(LoadError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            Game::from_save("bankroll=250\n"),
+            Err(LoadError::Missing("version"))
+        );
+        assert_eq!(
+            Game::from_save("version=1\nbankroll=250\nrules.double=sometimes\n"),
+            Err(LoadError::Invalid {
+                key: "rules.double".to_string(),
+                value: "sometimes".to_string()
+            })
+        );
+    }
+}
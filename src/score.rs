@@ -0,0 +1,26 @@
+//! # Score
+//!
+//! The tally of right answers kept by the `--coach` and `--count` modes.
+
+/// Answers that were right, out of every answer given
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Score {
+    pub correct: u32,
+    pub total: u32,
+}
+
+impl Score {
+    /// Count an answer. Returns `correct`
+    pub fn record(&mut self, correct: bool) -> bool {
+        self.total += 1;
+        if correct {
+            self.correct += 1;
+        }
+        correct
+    }
+
+    /// Percentage of right answers, `None` before the first one
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.total > 0).then(|| 100.0 * self.correct as f64 / self.total as f64)
+    }
+}
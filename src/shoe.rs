@@ -0,0 +1,432 @@
+//! # Shoe module
+//!
+//! This module contains the `Shoe` struct: several decks shuffled together,
+//! with a cut card that tells the dealer when to reshuffle between rounds.
+//! `Composition` counts the cards left in it by blackjack value, for odds calculations.
+
+use crate::{card::Card, deck::Deck};
+use rand::{SeedableRng, rngs::StdRng};
+
+/// Number of cards of each blackjack value: aces, then twos through nines, then ten-valued cards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Composition {
+    counts: [u16; 10],
+}
+
+impl Composition {
+    /// Constructor: The cards of `decks` full decks
+    pub fn full(decks: u8) -> Self {
+        let decks = decks.max(1) as u16;
+        let mut counts = [4 * decks; 10];
+        counts[9] = 16 * decks;
+
+        Composition { counts }
+    }
+
+    /// Constructor: Counts the given cards
+    pub fn from_cards(cards: &[Card]) -> Self {
+        let mut composition = Composition::default();
+        for card in cards {
+            composition.add(card);
+        }
+        composition
+    }
+
+    /// Index of a card value (2 - 11) in `counts`
+    fn index(value: u8) -> usize {
+        if value == 11 { 0 } else { value as usize - 1 }
+    }
+
+    /// Number of cards worth `value` (2 - 11, aces are 11; every ten-valued card counts as 10)
+    pub fn count(&self, value: u8) -> u16 {
+        self.counts[Composition::index(value)]
+    }
+
+    /// Total number of cards
+    pub fn total(&self) -> u32 {
+        self.counts.iter().map(|&count| count as u32).sum()
+    }
+
+    /// Chance that the next card drawn is worth `value`
+    pub fn probability(&self, value: u8) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => self.count(value) as f64 / total as f64,
+        }
+    }
+
+    pub fn add(&mut self, card: &Card) {
+        self.add_value(card.value());
+    }
+
+    /// Put a card worth `value` (2 - 11) in
+    pub fn add_value(&mut self, value: u8) {
+        self.counts[Composition::index(value)] += 1;
+    }
+
+    /// Take a card out, returns false if there was none of its value left
+    pub fn remove(&mut self, card: &Card) -> bool {
+        self.remove_value(card.value())
+    }
+
+    /// Take a card worth `value` (2 - 11) out, returns false if there was none left
+    pub fn remove_value(&mut self, value: u8) -> bool {
+        let count = &mut self.counts[Composition::index(value)];
+        if *count == 0 {
+            return false;
+        }
+        *count -= 1;
+        true
+    }
+}
+
+/// A dealing shoe holding one or more shuffled decks and a cut card
+///
+/// Every shuffle draws from the shoe's own seeded RNG, so two shoes built from
+/// the same seed deal exactly the same cards
+///
+/// With the `serde` feature, the RNG is left out and rebuilt from the seed and shuffle count on load
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "ShoeState")
+)]
+pub struct Shoe {
+    deck: Deck,
+    decks: u8,
+    /// Number of cards left in the shoe when the cut card comes out
+    cut_card: usize,
+    seed: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    rng: StdRng,
+    /// Number of times the shoe was shuffled from `seed`, which fixes the state of `rng`
+    shuffles: u32,
+    /// True for a scripted shoe, which deals its cards in order and is never shuffled
+    stacked: bool,
+}
+
+impl Shoe {
+    /// Constructor: Combines `decks` standard decks into one shuffled shoe, with a random seed
+    ///
+    /// The cut card is placed after `penetration` (0.0 - 1.0) of the cards
+    pub fn new(decks: u8, penetration: f32) -> Self {
+        Shoe::with_seed(decks, penetration, rand::random())
+    }
+
+    /// Constructor: Like `new()`, but shuffles reproducibly from the given `seed`
+    pub fn with_seed(decks: u8, penetration: f32, seed: u64) -> Self {
+        let decks = decks.max(1);
+        let total = 52 * decks as usize;
+        let dealt = (total as f32 * penetration.clamp(0.0, 1.0)).round() as usize;
+
+        let mut shoe = Shoe {
+            deck: Deck { cards: Vec::new() },
+            decks,
+            cut_card: total - dealt,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            shuffles: 0,
+            stacked: false,
+        };
+        shoe.shuffle();
+
+        shoe
+    }
+
+    /// Constructor: Rebuilds a saved shoe that was shuffled `shuffles` times from `seed`
+    /// and has `cards` left, next card first
+    ///
+    /// The shuffles are replayed, so the restored shoe goes on shuffling exactly like the saved one
+    pub fn restore(
+        decks: u8,
+        penetration: f32,
+        seed: u64,
+        shuffles: u32,
+        cards: Vec<Card>,
+    ) -> Self {
+        let mut shoe = Shoe::with_seed(decks, penetration, seed);
+        shoe.rng = Shoe::replay(shoe.decks, seed, shuffles);
+        shoe.shuffles = shuffles;
+        shoe.deck = Deck::from_cards(cards);
+
+        shoe
+    }
+
+    /// The RNG of a shoe of `decks` decks after `shuffles` shuffles from `seed`
+    fn replay(decks: u8, seed: u64, shuffles: u32) -> StdRng {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut deck = Deck { cards: Vec::new() };
+
+        for _ in 0..shuffles {
+            deck.cards.clear();
+            for _ in 0..decks {
+                deck.cards.extend(Deck::new().cards);
+            }
+            deck.shuffle_with(&mut rng);
+        }
+
+        rng
+    }
+
+    /// Constructor: Creates a scripted shoe that deals exactly `cards`, in order, first card first
+    ///
+    /// A stacked shoe is never shuffled and returns `None` once its cards run out,
+    /// which makes whole rounds reproducible in tests
+    pub fn stacked(cards: Vec<Card>) -> Self {
+        Shoe {
+            deck: Deck::from_cards(cards),
+            decks: 1,
+            cut_card: 0,
+            seed: 0,
+            rng: StdRng::seed_from_u64(0),
+            shuffles: 0,
+            stacked: true,
+        }
+    }
+
+    /// Gather every card back into the shoe and shuffle it (does nothing for a stacked shoe)
+    pub fn shuffle(&mut self) {
+        if self.stacked {
+            return;
+        }
+
+        self.deck.cards.clear();
+        for _ in 0..self.decks {
+            self.deck.cards.extend(Deck::new().cards);
+        }
+
+        self.deck.shuffle_with(&mut self.rng);
+        self.shuffles += 1;
+    }
+
+    /// Gather every card back into the shoe except those `in_play`, and shuffle it
+    ///
+    /// For a shoe that runs dry mid-round: the cards on the table stay there instead of
+    /// being dealt a second time. Draws from the seed exactly like `shuffle()`
+    pub fn shuffle_without(&mut self, in_play: &[Card]) {
+        if self.stacked {
+            return;
+        }
+
+        self.shuffle();
+        for card in in_play {
+            if let Some(i) = self.deck.cards.iter().position(|c| c == card) {
+                self.deck.cards.remove(i);
+            }
+        }
+    }
+
+    /// The seed this shoe's shuffles are drawn from
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Number of times the shoe was shuffled since it was built
+    pub fn shuffles(&self) -> u32 {
+        self.shuffles
+    }
+
+    /// The cards left to deal, next card first
+    pub fn cards(&self) -> Vec<Card> {
+        self.deck.cards.iter().rev().copied().collect()
+    }
+
+    /// Check if the cut card has come out, meaning the shoe should be shuffled before the next round
+    pub fn needs_shuffle(&self) -> bool {
+        !self.stacked && self.deck.cards.len() <= self.cut_card
+    }
+
+    /// Removes and returns the next card from the shoe, `None` once it is empty
+    ///
+    /// The shoe is never reshuffled mid-round because of the cut card; a game whose shoe
+    /// runs dry reshuffles it with `shuffle_without()`
+    pub fn deal(&mut self) -> Option<Card> {
+        self.deck.deal()
+    }
+
+    /// Number of cards left to deal
+    pub fn remaining(&self) -> usize {
+        self.deck.cards.len()
+    }
+
+    /// Count of the cards left to deal, by value
+    pub fn composition(&self) -> Composition {
+        Composition::from_cards(&self.deck.cards)
+    }
+
+    /// Number of decks the shoe holds when full
+    pub fn decks(&self) -> u8 {
+        self.decks
+    }
+}
+
+/// Every field of a `Shoe` but its RNG, as deserialized
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct ShoeState {
+    deck: Deck,
+    decks: u8,
+    cut_card: usize,
+    seed: u64,
+    shuffles: u32,
+    stacked: bool,
+}
+
+#[cfg(feature = "serde")]
+impl From<ShoeState> for Shoe {
+    fn from(state: ShoeState) -> Self {
+        Shoe {
+            rng: Shoe::replay(state.decks, state.seed, state.shuffles),
+            deck: state.deck,
+            decks: state.decks,
+            cut_card: state.cut_card,
+            seed: state.seed,
+            shuffles: state.shuffles,
+            stacked: state.stacked,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test `Shoe` initialization
+    ///
+    /// Creates shoes of 1, 2, 6 and 8 decks and asserts their size
+    #[test]
+    fn test_shoe_initialization() {
+        for decks in [1, 2, 6, 8] {
+            let shoe = Shoe::new(decks, 0.75);
+
+            assert_eq!(shoe.remaining(), 52 * decks as usize);
+            assert!(!shoe.needs_shuffle());
+        }
+    }
+
+    /// Test the cut card
+    ///
+    /// Creates a 6-deck shoe with 75% penetration, asserts the cut card comes out after
+    /// 234 of its 312 cards, and that dealing continues past it without a reshuffle
+    #[test]
+    fn test_cut_card() {
+        let mut shoe = Shoe::new(6, 0.75);
+
+        for _ in 0..233 {
+            shoe.deal();
+        }
+        assert!(!shoe.needs_shuffle());
+
+        shoe.deal();
+        assert!(shoe.needs_shuffle());
+
+        shoe.deal();
+        assert_eq!(shoe.remaining(), 77);
+
+        shoe.shuffle();
+        assert_eq!(shoe.remaining(), 312);
+        assert!(!shoe.needs_shuffle());
+    }
+
+    /// Test dealing an entire shoe
+    ///
+    /// Deals every card of a single-deck shoe, asserts it then runs dry, and that
+    /// reshuffling it leaves out the cards still in play
+    #[test]
+    fn test_deal_past_empty() {
+        let mut shoe = Shoe::new(1, 0.6);
+
+        let dealt: Vec<Card> = (0..52).map(|_| shoe.deal().unwrap()).collect();
+        assert_eq!(shoe.deal(), None);
+
+        let in_play = &dealt[..3];
+        shoe.shuffle_without(in_play);
+        assert_eq!(shoe.remaining(), 49);
+        assert!(shoe.cards().iter().all(|card| !in_play.contains(card)));
+    }
+
+    /// Test seeded shoes
+    ///
+    /// Builds two shoes from the same seed and asserts they deal identical cards, across a reshuffle
+    #[test]
+    fn test_seeded_shoe() {
+        let mut s1 = Shoe::with_seed(2, 0.75, 7);
+        let mut s2 = Shoe::with_seed(2, 0.75, 7);
+
+        for _ in 0..200 {
+            assert_eq!(s1.deal(), s2.deal());
+        }
+        s1.shuffle();
+        s2.shuffle();
+        assert_eq!(s1, s2);
+        assert_eq!(s1.seed(), 7);
+
+        let s3 = Shoe::with_seed(2, 0.75, 8);
+        assert_ne!(s1.deck, s3.deck);
+    }
+
+    /// Test restoring a shoe
+    ///
+    /// Deals into a second shoe, restores it from its seed, shuffle count and cards,
+    /// and asserts the copy deals the same cards across the next reshuffle
+    #[test]
+    fn test_restore_shoe() {
+        let mut shoe = Shoe::with_seed(1, 0.75, 3);
+        for _ in 0..40 {
+            shoe.deal();
+        }
+        shoe.shuffle_without(&[]);
+        for _ in 0..30 {
+            shoe.deal();
+        }
+        assert_eq!(shoe.shuffles(), 2);
+
+        let mut restored = Shoe::restore(1, 0.75, 3, shoe.shuffles(), shoe.cards());
+        assert_eq!(restored, shoe);
+
+        shoe.shuffle();
+        restored.shuffle();
+        for _ in 0..52 {
+            assert_eq!(restored.deal(), shoe.deal());
+        }
+    }
+
+    /// Test stacked shoes
+    ///
+    /// Stacks a shoe with two cards, asserts they are dealt in order,
+    /// and that the shoe is never shuffled or refilled
+    #[test]
+    fn test_stacked_shoe() {
+        let cards = crate::card::parse_cards("AS KH").unwrap();
+        let mut shoe = Shoe::stacked(cards.clone());
+
+        assert!(!shoe.needs_shuffle());
+        assert_eq!(shoe.deal(), Some(cards[0]));
+
+        shoe.shuffle();
+        assert_eq!(shoe.remaining(), 1);
+        assert_eq!(shoe.deal(), Some(cards[1]));
+        assert_eq!(shoe.deal(), None);
+    }
+
+    /// Test shoe composition
+    ///
+    /// Asserts a full 6-deck shoe holds 24 of each low card and 96 ten-valued cards,
+    /// and that dealt cards leave the composition
+    #[test]
+    fn test_composition() {
+        let mut shoe = Shoe::with_seed(6, 0.75, 1);
+        assert_eq!(shoe.composition(), Composition::full(6));
+        assert_eq!(shoe.composition().count(10), 96);
+        assert_eq!(shoe.composition().count(11), 24);
+
+        let card = shoe.deal().unwrap();
+        let mut expected = Composition::full(6);
+        assert!(expected.remove(&card));
+
+        assert_eq!(shoe.composition(), expected);
+        assert_eq!(expected.total(), 311);
+    }
+}
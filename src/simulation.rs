@@ -0,0 +1,518 @@
+//! # Simulation
+//!
+//! This module plays many rounds with a `Strategy` and no terminal I/O,
+//! to measure the expected value (house edge) of a set of table rules.
+//!
+//! Large runs are split into fixed-size chunks, each shuffled from a seed derived from the
+//! simulation's seed and the chunk's index. Threads play chunks in any order, and the results
+//! are merged in chunk order, so a seeded run gives the same report on any number of threads.
+//!
+//! Sessions measure a betting system instead: each one plays with a real bankroll from its
+//! own derived seed until the player is ruined or the rounds run out.
+
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    thread,
+};
+
+use crate::{
+    game::{Game, RoundResult},
+    round::{Event, GameError},
+    rules::TableRules,
+    strategy::Strategy,
+};
+
+/// Bankroll the simulated player starts every round with, so results measure the game and not ruin
+const BANKROLL: u32 = 1_000_000_000;
+
+/// Rounds played from each derived seed by `Simulation::run_parallel()`
+const CHUNK_ROUNDS: u64 = 100_000;
+
+/// Derive the seed of chunk `chunk` from a simulation's seed (SplitMix64)
+pub fn chunk_seed(seed: u64, chunk: u64) -> u64 {
+    let mut z = seed.wrapping_add((chunk + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Running mean and variance of a series of values (Welford's algorithm)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RunningStats {
+    count: u64,
+    mean: f64,
+    /// Sum of squared differences from the mean
+    m2: f64,
+}
+
+impl RunningStats {
+    /// Constructor: Empty series
+    pub fn new() -> Self {
+        RunningStats::default()
+    }
+
+    /// Add a value to the series
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Combine with the stats of another series, as if its values had been pushed here (Chan et al.)
+    pub fn merge(&mut self, other: &RunningStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = *other;
+            return;
+        }
+
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        let weight = self.count as f64 * other.count as f64 / count as f64;
+
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * weight;
+        self.count = count;
+    }
+
+    /// Number of values in the series
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Sample variance, 0 for fewer than two values
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Standard error of the mean
+    pub fn std_error(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.std_dev() / (self.count as f64).sqrt()
+        }
+    }
+
+    /// 95% confidence interval of the mean
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        let margin = 1.96 * self.std_error();
+        (self.mean - margin, self.mean + margin)
+    }
+}
+
+/// Results of a simulation
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SimulationReport {
+    /// Net result of each round, in units of the round's opening bet
+    pub net: RunningStats,
+    /// Hands settled, counting each split hand
+    pub hands: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub pushes: u64,
+    pub blackjacks: u64,
+    pub surrenders: u64,
+}
+
+impl SimulationReport {
+    /// Number of rounds played
+    pub fn rounds(&self) -> u64 {
+        self.net.count()
+    }
+
+    /// Expected value per round, as a fraction of the opening bet (negative is the house edge)
+    pub fn ev(&self) -> f64 {
+        self.net.mean()
+    }
+
+    /// Fraction of settled hands that `count` makes up, e.g. `report.frequency(report.wins)`
+    pub fn frequency(&self, count: u64) -> f64 {
+        if self.hands == 0 {
+            0.0
+        } else {
+            count as f64 / self.hands as f64
+        }
+    }
+
+    /// Add the results of another report
+    pub fn merge(&mut self, other: &SimulationReport) {
+        self.net.merge(&other.net);
+        self.hands += other.hands;
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.pushes += other.pushes;
+        self.blackjacks += other.blackjacks;
+        self.surrenders += other.surrenders;
+    }
+
+    /// Record the events of one round played for an opening `bet`
+    fn record(&mut self, bet: u32, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::HandSettled { result, .. } => {
+                    self.hands += 1;
+                    match result {
+                        RoundResult::PlayerWin => self.wins += 1,
+                        RoundResult::DealerWin => self.losses += 1,
+                        RoundResult::Push => self.pushes += 1,
+                        RoundResult::PlayerBlackjack => self.blackjacks += 1,
+                        RoundResult::Surrender => self.surrenders += 1,
+                    }
+                }
+                Event::RoundOver { net, .. } => self.net.push(*net as f64 / bet as f64),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Results of playing many sessions, each from the same starting bankroll
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BankrollReport {
+    /// Bankroll every session starts with
+    pub bankroll: u32,
+    /// Bankroll at the end of each session
+    pub final_bankroll: RunningStats,
+    /// Rounds played in each session
+    pub rounds: RunningStats,
+    /// Average bet of each session
+    pub average_bet: RunningStats,
+    /// Sessions that ended with less than the table minimum left
+    pub ruined: u64,
+}
+
+impl BankrollReport {
+    pub fn sessions(&self) -> u64 {
+        self.final_bankroll.count()
+    }
+
+    /// Fraction of sessions that ended in ruin
+    pub fn risk_of_ruin(&self) -> f64 {
+        if self.sessions() == 0 {
+            0.0
+        } else {
+            self.ruined as f64 / self.sessions() as f64
+        }
+    }
+
+    /// Average growth of the bankroll over a session, as a fraction of the starting bankroll
+    pub fn growth(&self) -> f64 {
+        self.final_bankroll.mean() / self.bankroll as f64 - 1.0
+    }
+
+    /// Add the results of another report from the same starting bankroll
+    pub fn merge(&mut self, other: &BankrollReport) {
+        self.final_bankroll.merge(&other.final_bankroll);
+        self.rounds.merge(&other.rounds);
+        self.average_bet.merge(&other.average_bet);
+        self.ruined += other.ruined;
+    }
+}
+
+/// A headless run of many rounds under one set of table rules
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simulation {
+    pub rules: TableRules,
+    pub rounds: u64,
+    pub seed: u64,
+    /// Rounds played from each derived seed by `run_parallel()`
+    pub chunk_rounds: u64,
+}
+
+impl Simulation {
+    /// Constructor: Plays `rounds` rounds under `rules`, shuffling from a random seed
+    pub fn new(rules: TableRules, rounds: u64) -> Self {
+        Simulation::with_seed(rules, rounds, rand::random())
+    }
+
+    /// Constructor: Like `new()`, but shuffles reproducibly from the given `seed`
+    pub fn with_seed(rules: TableRules, rounds: u64, seed: u64) -> Self {
+        Simulation {
+            rules,
+            rounds,
+            seed,
+            chunk_rounds: CHUNK_ROUNDS,
+        }
+    }
+
+    /// Play every round with `strategy` and report the results
+    ///
+    /// Returns the first error if the strategy makes a move the game rejects
+    pub fn run(&self, strategy: &mut dyn Strategy) -> Result<SimulationReport, GameError> {
+        let mut game = Game::with_seed(BANKROLL, self.rules.clone(), self.seed);
+        let mut report = SimulationReport::default();
+
+        for _ in 0..self.rounds {
+            game.bankroll = BANKROLL;
+
+            let events = game.play_round(strategy)?;
+            report.record(game.bet, &events);
+        }
+
+        Ok(report)
+    }
+
+    /// Play every round across `threads` threads, each chunk with a fresh strategy from `new_strategy`
+    ///
+    /// The report only depends on the seed and the chunk size, never on the number of threads.
+    /// Returns the error of the first failing chunk if the strategy makes a move the game rejects
+    pub fn run_parallel<S: Strategy>(
+        &self,
+        threads: usize,
+        new_strategy: impl Fn() -> S + Sync,
+    ) -> Result<SimulationReport, GameError> {
+        let chunk_rounds = self.chunk_rounds.max(1);
+        let chunks = self.rounds.div_ceil(chunk_rounds);
+
+        let results = in_parallel(threads, chunks, |chunk| {
+            let rounds = chunk_rounds.min(self.rounds - chunk * chunk_rounds);
+            let seed = chunk_seed(self.seed, chunk);
+
+            Simulation::with_seed(self.rules.clone(), rounds, seed).run(&mut new_strategy())
+        });
+
+        let mut report = SimulationReport::default();
+        for result in results {
+            report.merge(&result?);
+        }
+
+        Ok(report)
+    }
+
+    /// Play `sessions` sessions of up to `rounds` rounds each, starting from `bankroll`,
+    /// across `threads` threads, each session with a fresh strategy from `new_strategy`
+    ///
+    /// A session ends early once the bankroll drops below the table minimum.
+    /// Like `run_parallel()`, the report only depends on the seed.
+    /// Returns the error of the first failing session if the strategy makes a move the game rejects
+    pub fn run_sessions<S: Strategy>(
+        &self,
+        sessions: u64,
+        bankroll: u32,
+        threads: usize,
+        new_strategy: impl Fn() -> S + Sync,
+    ) -> Result<BankrollReport, GameError> {
+        let results = in_parallel(threads, sessions, |session| {
+            let seed = chunk_seed(self.seed, session);
+            self.run_session(bankroll, seed, &mut new_strategy())
+        });
+
+        let mut report = BankrollReport {
+            bankroll,
+            ..BankrollReport::default()
+        };
+        for result in results {
+            report.merge(&result?);
+        }
+
+        Ok(report)
+    }
+
+    /// Play one session from `bankroll`, shuffling from `seed`
+    fn run_session(
+        &self,
+        bankroll: u32,
+        seed: u64,
+        strategy: &mut dyn Strategy,
+    ) -> Result<BankrollReport, GameError> {
+        let mut game = Game::with_seed(bankroll, self.rules.clone(), seed);
+        let mut bets = RunningStats::new();
+
+        while bets.count() < self.rounds && game.bankroll >= self.rules.min_bet {
+            game.play_round(strategy)?;
+            bets.push(game.bet as f64);
+        }
+
+        let mut report = BankrollReport {
+            bankroll,
+            ruined: (game.bankroll < self.rules.min_bet) as u64,
+            ..BankrollReport::default()
+        };
+        report.final_bankroll.push(game.bankroll as f64);
+        report.rounds.push(bets.count() as f64);
+        report.average_bet.push(bets.mean());
+
+        Ok(report)
+    }
+}
+
+/// Run `job` for every index below `jobs` across `threads` threads, and return the results in index order
+fn in_parallel<T: Send>(threads: usize, jobs: u64, job: impl Fn(u64) -> T + Sync) -> Vec<T> {
+    let next_job = AtomicU64::new(0);
+    let results = Mutex::new((0..jobs).map(|_| None).collect::<Vec<_>>());
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, jobs.max(1) as usize) {
+            scope.spawn(|| {
+                loop {
+                    let i = next_job.fetch_add(1, Ordering::Relaxed);
+                    if i >= jobs {
+                        break;
+                    }
+
+                    let result = job(i);
+                    results.lock().expect("a simulation thread panicked")[i as usize] =
+                        Some(result);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .expect("a simulation thread panicked")
+        .into_iter()
+        .map(|result| result.expect("every job is run"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        basic_strategy::BasicStrategy,
+        betting::{Betting, Flat, Martingale},
+        strategy::MimicDealer,
+    };
+
+    /// Test `RunningStats`
+    ///
+    /// Pushes a known series and asserts its mean, sample variance and confidence interval
+    #[test]
+    fn test_running_stats() {
+        let mut stats = RunningStats::new();
+        for value in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            stats.push(value);
+        }
+
+        assert_eq!(stats.count(), 8);
+        assert!((stats.mean() - 5.0).abs() < 1e-12);
+        assert!((stats.variance() - 32.0 / 7.0).abs() < 1e-12);
+
+        let (low, high) = stats.confidence_interval();
+        assert!((high - low - 2.0 * 1.96 * stats.std_error()).abs() < 1e-12);
+        assert!(low < 5.0 && high > 5.0);
+    }
+
+    /// Test merging `RunningStats`
+    ///
+    /// Splits a series in two, merges the halves, and asserts it matches the whole series
+    #[test]
+    fn test_merge_stats() {
+        let values = [1.5, -1.0, -1.0, 2.0, 0.0, 1.0, -2.0, 1.0, -1.0];
+        let mut whole = RunningStats::new();
+        let mut first = RunningStats::new();
+        let mut second = RunningStats::new();
+
+        for (i, &value) in values.iter().enumerate() {
+            whole.push(value);
+            if i < 4 {
+                first.push(value);
+            } else {
+                second.push(value);
+            }
+        }
+        first.merge(&second);
+
+        assert_eq!(first.count(), whole.count());
+        assert!((first.mean() - whole.mean()).abs() < 1e-12);
+        assert!((first.variance() - whole.variance()).abs() < 1e-12);
+    }
+
+    /// Test parallel simulations
+    ///
+    /// Runs a seeded simulation in uneven chunks on 1, 3 and 8 threads and asserts identical reports
+    #[test]
+    fn test_parallel_simulation() {
+        let mut simulation = Simulation::with_seed(TableRules::default(), 2100, 9);
+        simulation.chunk_rounds = 500;
+
+        let report = simulation.run_parallel(1, || MimicDealer).unwrap();
+
+        assert_eq!(report.rounds(), 2100);
+        assert_eq!(simulation.run_parallel(3, || MimicDealer).unwrap(), report);
+        assert_eq!(simulation.run_parallel(8, || MimicDealer).unwrap(), report);
+        assert_ne!(chunk_seed(9, 0), chunk_seed(9, 1));
+    }
+
+    /// Test a seeded simulation
+    ///
+    /// Runs the same seeded simulation twice and asserts identical reports,
+    /// with every hand counted once
+    #[test]
+    fn test_seeded_simulation() {
+        let simulation = Simulation::with_seed(TableRules::vegas_strip(), 2000, 42);
+
+        let report = simulation.run(&mut MimicDealer).unwrap();
+        let again = simulation.run(&mut MimicDealer).unwrap();
+
+        assert_eq!(report, again);
+        assert_eq!(report.rounds(), 2000);
+        assert_eq!(
+            report.hands,
+            report.wins + report.losses + report.pushes + report.blackjacks + report.surrenders
+        );
+    }
+
+    /// Test the house edge against a dealer mimic
+    ///
+    /// Mimicking the dealer gives up about 5% to the house; basic strategy loses far less
+    #[test]
+    fn test_house_edge() {
+        let simulation = Simulation::with_seed(TableRules::vegas_strip(), 20_000, 7);
+
+        let mimic = simulation.run(&mut MimicDealer).unwrap();
+        let (_, high) = mimic.net.confidence_interval();
+        assert!(high < 0.0);
+
+        let basic = simulation
+            .run(&mut BasicStrategy::new(&simulation.rules))
+            .unwrap();
+        assert!(basic.ev() > mimic.ev());
+    }
+
+    /// Test bankroll sessions
+    ///
+    /// Plays Martingale sessions from a small bankroll on 1 and 4 threads, asserts identical
+    /// reports, and that the doubling ruins more sessions than flat betting
+    #[test]
+    fn test_sessions() {
+        let simulation = Simulation::with_seed(TableRules::vegas_strip(), 300, 5);
+        let basic = BasicStrategy::new(&simulation.rules);
+        let martingale = || Betting::new(basic.clone(), Martingale::default());
+        let flat = || Betting::new(basic.clone(), Flat { units: 1 });
+
+        let report = simulation.run_sessions(40, 500, 1, martingale).unwrap();
+        assert_eq!(report.sessions(), 40);
+        assert_eq!(
+            simulation.run_sessions(40, 500, 4, martingale).unwrap(),
+            report
+        );
+        assert!(report.risk_of_ruin() > 0.0);
+
+        let flat = simulation.run_sessions(40, 500, 1, flat).unwrap();
+        assert!(flat.risk_of_ruin() < report.risk_of_ruin());
+        assert!(flat.rounds.mean() > report.rounds.mean());
+        assert_eq!(flat.average_bet.mean(), 10.0);
+    }
+}
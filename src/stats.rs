@@ -0,0 +1,148 @@
+//! # Stats
+//!
+//! This module contains `SessionStats`, the running tally of a session: hands played and
+//! how they ended, doubles and splits, the biggest swings, streaks and the net result.
+
+use crate::{
+    game::{Game, RoundResult},
+    round::Event,
+};
+
+/// Everything that happened in a session, updated once per round with `record()`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SessionStats {
+    pub rounds: u32,
+    /// Hands settled, counting each split hand
+    pub hands: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub pushes: u32,
+    pub blackjacks: u32,
+    pub surrenders: u32,
+    /// Player hands that went over 21
+    pub busts: u32,
+    pub doubles: u32,
+    pub splits: u32,
+    /// Most won in a single round
+    pub biggest_win: u32,
+    /// Most lost in a single round
+    pub biggest_loss: u32,
+    /// Most rounds won in a row
+    pub longest_win_streak: u32,
+    /// Most rounds lost in a row
+    pub longest_loss_streak: u32,
+    /// Total won (or lost, if negative) over the session
+    pub net: i64,
+    /// Rounds won in a row so far (positive) or lost in a row (negative); a push ends either
+    pub(crate) streak: i64,
+}
+
+impl SessionStats {
+    /// Record a finished round from its `events`, with `game` still holding its hands
+    pub fn record(&mut self, game: &Game, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::Doubled { .. } => self.doubles += 1,
+                Event::Split { .. } => self.splits += 1,
+                Event::HandSettled { hand, result, .. } => {
+                    self.hands += 1;
+                    match result {
+                        RoundResult::PlayerWin => self.wins += 1,
+                        RoundResult::DealerWin => self.losses += 1,
+                        RoundResult::Push => self.pushes += 1,
+                        RoundResult::PlayerBlackjack => self.blackjacks += 1,
+                        RoundResult::Surrender => self.surrenders += 1,
+                    }
+
+                    if game
+                        .player_hands
+                        .get(*hand)
+                        .is_some_and(|player| player.hand.value() > 21)
+                    {
+                        self.busts += 1;
+                    }
+                }
+                Event::RoundOver { net, .. } => self.finish_round(*net),
+                _ => {}
+            }
+        }
+    }
+
+    /// Add the tally of another session, e.g. to keep lifetime statistics
+    ///
+    /// Streaks are not joined across sessions: the longest of either is kept
+    pub fn merge(&mut self, other: &SessionStats) {
+        self.rounds += other.rounds;
+        self.hands += other.hands;
+        self.wins += other.wins;
+        self.losses += other.losses;
+        self.pushes += other.pushes;
+        self.blackjacks += other.blackjacks;
+        self.surrenders += other.surrenders;
+        self.busts += other.busts;
+        self.doubles += other.doubles;
+        self.splits += other.splits;
+        self.biggest_win = self.biggest_win.max(other.biggest_win);
+        self.biggest_loss = self.biggest_loss.max(other.biggest_loss);
+        self.longest_win_streak = self.longest_win_streak.max(other.longest_win_streak);
+        self.longest_loss_streak = self.longest_loss_streak.max(other.longest_loss_streak);
+        self.net += other.net;
+        self.streak = 0;
+    }
+
+    /// Count a round that ended `net` up or down
+    fn finish_round(&mut self, net: i64) {
+        self.rounds += 1;
+        self.net += net;
+
+        self.streak = match net {
+            0 => 0,
+            _ if net > 0 => self.streak.max(0) + 1,
+            _ => self.streak.min(0) - 1,
+        };
+        if net > 0 {
+            self.biggest_win = self.biggest_win.max(net as u32);
+            self.longest_win_streak = self.longest_win_streak.max(self.streak as u32);
+        } else if net < 0 {
+            self.biggest_loss = self.biggest_loss.max(net.unsigned_abs() as u32);
+            self.longest_loss_streak = self
+                .longest_loss_streak
+                .max(self.streak.unsigned_abs() as u32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rules::TableRules, strategy::MimicDealer};
+
+    /// Test the session tally
+    ///
+    /// Scripts a win, a blackjack and a bust for the `MimicDealer` bot, and asserts the
+    /// counts, biggest swings, streaks and net result
+    #[test]
+    fn test_session_stats() {
+        let mut game = Game::scripted(
+            TableRules::vegas_strip(),
+            "TH TS 2C 8D 7H AC 9D KC 7D TC TD 6C 7D TH",
+        );
+        let mut stats = SessionStats::default();
+
+        for _ in 0..3 {
+            let events = game.play_round(&mut MimicDealer).unwrap();
+            stats.record(&game, &events);
+        }
+
+        assert_eq!((stats.rounds, stats.hands), (3, 3));
+        assert_eq!((stats.wins, stats.blackjacks, stats.losses), (1, 1, 1));
+        assert_eq!(stats.busts, 1);
+        assert_eq!((stats.biggest_win, stats.biggest_loss), (15, 10));
+        assert_eq!(
+            (stats.longest_win_streak, stats.longest_loss_streak),
+            (2, 1)
+        );
+        assert_eq!(stats.net, 15);
+        assert_eq!(game.bankroll, 1015);
+    }
+}
@@ -0,0 +1,191 @@
+//! # Storage
+//!
+//! Keeps the player's session on disk between runs, in `rust-blackjack-cli/session.txt`
+//! under `$XDG_DATA_HOME`, or `~/.local/share` if it isn't set.
+//! Hand histories go next to it, one file per session in `rust-blackjack-cli/history/`,
+//! and the lifetime record of the profile in `rust-blackjack-cli/profile.txt`.
+//!
+//! Every profile but the default one keeps the same files in `rust-blackjack-cli/profiles/<name>/`.
+
+use crate::profile::{DEFAULT_PROFILE, Profile};
+use blackjack::{Game, LoadError, SessionStats};
+use std::{
+    env,
+    ffi::OsString,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Directory under the data directory that holds this app's files
+const APP_DIR: &str = "rust-blackjack-cli";
+
+/// File the session is saved to
+const SESSION_FILE: &str = "session.txt";
+
+/// Directory under a profile's directory that holds the hand histories
+const HISTORY_DIR: &str = "history";
+
+/// File a profile's lifetime record is saved to
+const PROFILE_FILE: &str = "profile.txt";
+
+/// Directory under the app's directory that holds the profiles other than the default one
+const PROFILES_DIR: &str = "profiles";
+
+/// The user's data directory, from `$XDG_DATA_HOME` or `$HOME`
+///
+/// A relative `$XDG_DATA_HOME` is ignored, as the XDG spec asks. Returns `None` if neither is usable
+fn data_dir(xdg_data_home: Option<OsString>, home: Option<OsString>) -> Option<PathBuf> {
+    let xdg = xdg_data_home
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute());
+    let home = home
+        .filter(|home| !home.is_empty())
+        .map(|home| Path::new(&home).join(".local").join("share"));
+
+    xdg.or(home)
+}
+
+/// This app's directory under the data directory, `None` if there is no data directory
+fn app_dir() -> Option<PathBuf> {
+    data_dir(env::var_os("XDG_DATA_HOME"), env::var_os("HOME")).map(|dir| dir.join(APP_DIR))
+}
+
+/// Directory holding the files of `profile`, `None` if there is no data directory
+fn profile_dir(profile: &str) -> Option<PathBuf> {
+    let dir = app_dir()?;
+
+    if profile == DEFAULT_PROFILE {
+        Some(dir)
+    } else {
+        Some(dir.join(PROFILES_DIR).join(profile))
+    }
+}
+
+/// Names of the profiles that have been played, the default one first
+pub fn profile_names() -> Vec<String> {
+    let mut names: Vec<String> = app_dir()
+        .and_then(|dir| fs::read_dir(dir.join(PROFILES_DIR)).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|name| Profile::is_valid_name(name) && name != DEFAULT_PROFILE)
+        .collect();
+    names.sort();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    names
+}
+
+/// Load the profile called `name`, or a new one if it hasn't played yet
+pub fn load_profile(name: &str) -> io::Result<Profile> {
+    let path = profile_dir(name)
+        .ok_or(io::ErrorKind::NotFound)?
+        .join(PROFILE_FILE);
+
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Profile::from_text(name, &text)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Profile::new(name)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Save the profile, replacing its earlier record
+pub fn save_profile(profile: &Profile) -> io::Result<()> {
+    let path = profile_dir(&profile.name)
+        .ok_or(io::ErrorKind::NotFound)?
+        .join(PROFILE_FILE);
+    write_replacing(&path, &profile.to_text())
+}
+
+/// Path of the saved session of `profile`, `None` if there is no data directory
+pub fn session_path(profile: &str) -> Option<PathBuf> {
+    profile_dir(profile).map(|dir| dir.join(SESSION_FILE))
+}
+
+/// Create the hand history file of a new session of `profile`, named after the time and the shoe's `seed`
+pub fn create_history(profile: &str, seed: u64) -> io::Result<(PathBuf, File)> {
+    let dir = profile_dir(profile)
+        .ok_or(io::ErrorKind::NotFound)?
+        .join(HISTORY_DIR);
+    fs::create_dir_all(&dir)?;
+
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let path = dir.join(format!("{}-{}.jsonl", started, seed));
+    let file = File::create(&path)?;
+
+    Ok((path, file))
+}
+
+/// Path of the most recent hand history of `profile`, `None` if there is none
+pub fn latest_history(profile: &str) -> Option<PathBuf> {
+    fs::read_dir(profile_dir(profile)?.join(HISTORY_DIR))
+        .ok()?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .max_by_key(|path| {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            let started: u64 = name.split('-').next().unwrap_or("").parse().unwrap_or(0);
+            (started, name.into_owned())
+        })
+}
+
+/// Save the session of `profile` with its statistics so far, replacing any earlier save
+pub fn save(profile: &str, game: &Game, stats: &SessionStats) -> io::Result<()> {
+    let path = session_path(profile).ok_or(io::ErrorKind::NotFound)?;
+    write_replacing(&path, &(game.to_save() + &stats.to_save()))
+}
+
+/// Load the saved session of `profile` and its statistics. Returns `None` if there is none
+pub fn load(profile: &str) -> Option<Result<(Game, SessionStats), LoadError>> {
+    let text = fs::read_to_string(session_path(profile)?).ok()?;
+    Some(Game::from_save(&text).and_then(|game| Ok((game, SessionStats::from_save(&text)?))))
+}
+
+/// Delete the saved session of `profile`, if there is one
+pub fn delete(profile: &str) -> io::Result<()> {
+    match session_path(profile).map(fs::remove_file) {
+        Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Write `text` to `path`, creating its directory
+///
+/// The file is written next to the old one first, so a crash never leaves half a file behind
+fn write_replacing(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, text)?;
+    fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test finding the data directory
+    ///
+    /// Asserts `$XDG_DATA_HOME` wins over `$HOME`, and that a relative one is ignored
+    #[test]
+    fn test_data_dir() {
+        let xdg = || Some(OsString::from("/data"));
+        let home = || Some(OsString::from("/home/ace"));
+
+        assert_eq!(data_dir(xdg(), home()), Some(PathBuf::from("/data")));
+        assert_eq!(
+            data_dir(None, home()),
+            Some(PathBuf::from("/home/ace/.local/share"))
+        );
+        assert_eq!(
+            data_dir(Some(OsString::from("data")), home()),
+            Some(PathBuf::from("/home/ace/.local/share"))
+        );
+        assert_eq!(data_dir(None, None), None);
+    }
+}
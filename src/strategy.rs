@@ -0,0 +1,206 @@
+//! # Strategy
+//!
+//! This module contains the `Strategy` trait, the abstraction for "who decides":
+//! how much to bet, which action to take, and whether to accept an offer.
+//!
+//! Anything that implements it — a person at a terminal, a bot, a remote client —
+//! can play rounds through `Game::play_round()`.
+
+use crate::{
+    card::Card,
+    game::{Action, Game},
+    hand::Hand,
+    round::{Event, GameError, Offer, Phase},
+    rules::TableRules,
+};
+
+/// What a player can see about the table when deciding on a bet or an offer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TableView<'a> {
+    pub bankroll: u32,
+    /// Number of rounds played so far
+    pub round: u16,
+    pub rules: &'a TableRules,
+    /// Seed the shoe is shuffled from
+    pub seed: u64,
+    /// Cards left in the shoe
+    pub shoe_remaining: usize,
+    /// The cut card is out, so the shoe is shuffled before the next deal
+    pub shuffle_due: bool,
+    /// The bet riding on the current round (0 before the first bet)
+    pub bet: u32,
+    /// Largest insurance bet allowed right now
+    pub max_insurance: u32,
+}
+
+impl TableView<'_> {
+    /// Make `bet` valid: at least the table minimum, and at most the table maximum and the bankroll
+    pub fn clamp_bet(&self, bet: u32) -> u32 {
+        bet.clamp(self.rules.min_bet, self.rules.max_bet)
+            .min(self.bankroll)
+    }
+}
+
+/// A decision maker that can play blackjack
+pub trait Strategy {
+    /// Choose the bet for the next round. It must be valid for the table and bankroll
+    fn choose_bet(&mut self, table: &TableView) -> u32;
+
+    /// Choose an action for `hand` from `allowed`, given the dealer's face-up card
+    fn choose_action(&mut self, hand: &Hand, dealer_upcard: Card, allowed: &[Action]) -> Action;
+
+    /// Decide on an offer made before play starts. Declines everything by default
+    fn choose_offer(
+        &mut self,
+        _offer: Offer,
+        _hand: &Hand,
+        _dealer_upcard: Card,
+        _table: &TableView,
+    ) -> Action {
+        Action::Decline
+    }
+
+    /// Called with the events of every step of the round, e.g. to show or count them
+    fn observe(&mut self, _game: &Game, _events: &[Event]) {}
+}
+
+impl<S: Strategy + ?Sized> Strategy for Box<S> {
+    fn choose_bet(&mut self, table: &TableView) -> u32 {
+        (**self).choose_bet(table)
+    }
+
+    fn choose_action(&mut self, hand: &Hand, dealer_upcard: Card, allowed: &[Action]) -> Action {
+        (**self).choose_action(hand, dealer_upcard, allowed)
+    }
+
+    fn choose_offer(
+        &mut self,
+        offer: Offer,
+        hand: &Hand,
+        dealer_upcard: Card,
+        table: &TableView,
+    ) -> Action {
+        (**self).choose_offer(offer, hand, dealer_upcard, table)
+    }
+
+    fn observe(&mut self, game: &Game, events: &[Event]) {
+        (**self).observe(game, events);
+    }
+}
+
+/// A simple bot that plays like the dealer: bets the table minimum,
+/// hits below 17, stands on 17 or more, and declines every offer
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct MimicDealer;
+
+impl Strategy for MimicDealer {
+    fn choose_bet(&mut self, table: &TableView) -> u32 {
+        table.rules.min_bet
+    }
+
+    fn choose_action(&mut self, hand: &Hand, _dealer_upcard: Card, allowed: &[Action]) -> Action {
+        if hand.value() < 17 && allowed.contains(&Action::Hit) {
+            Action::Hit
+        } else {
+            Action::Stand
+        }
+    }
+}
+
+impl Game {
+    /// Returns what a player can see about the table right now
+    pub fn table_view(&self) -> TableView<'_> {
+        TableView {
+            bankroll: self.bankroll,
+            round: self.i,
+            rules: &self.rules,
+            seed: self.seed(),
+            shoe_remaining: self.shoe().remaining(),
+            shuffle_due: self.shoe().needs_shuffle(),
+            bet: self.bet,
+            max_insurance: self.max_insurance(),
+        }
+    }
+
+    /// Play one full round, asking `strategy` for every decision
+    ///
+    /// Returns all events of the round, or the first action the game rejected
+    pub fn play_round(&mut self, strategy: &mut dyn Strategy) -> Result<Vec<Event>, GameError> {
+        let bet = strategy.choose_bet(&self.table_view());
+        let mut events = self.apply(Action::Bet(bet))?;
+        strategy.observe(self, &events);
+
+        loop {
+            let action = match self.phase() {
+                Phase::Dealing => {
+                    let offer = self.offer().expect("dealing phase always has an offer");
+                    strategy.choose_offer(
+                        offer,
+                        self.player_hand(),
+                        self.dealer_hand.cards[0],
+                        &self.table_view(),
+                    )
+                }
+                Phase::PlayerTurn => strategy.choose_action(
+                    self.player_hand(),
+                    self.dealer_hand.cards[0],
+                    &self.allowed_actions(),
+                ),
+                _ => return Ok(events),
+            };
+
+            let step = self.apply(action)?;
+            strategy.observe(self, &step);
+            events.extend(step);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::RoundResult;
+
+    /// Test `play_round()` with the `MimicDealer` bot
+    ///
+    /// Scripts a round where the bot hits 12 to 19 and stands, asserts it wins against a dealer 18
+    #[test]
+    fn test_mimic_dealer_round() {
+        let mut game = Game::scripted(TableRules::vegas_strip(), "TH TS 2C 8D 7H");
+
+        let events = game.play_round(&mut MimicDealer).unwrap();
+
+        assert_eq!(game.phase(), Phase::Settlement);
+        assert_eq!(game.player_hand().value(), 19);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::HandSettled {
+                result: RoundResult::PlayerWin,
+                ..
+            }
+        )));
+        assert_eq!(game.bankroll, 1010);
+    }
+
+    /// Test an invalid bet from a strategy
+    ///
+    /// A strategy betting more than the bankroll is rejected with `InvalidBet`
+    #[test]
+    fn test_invalid_strategy_bet() {
+        struct AllIn;
+
+        impl Strategy for AllIn {
+            fn choose_bet(&mut self, table: &TableView) -> u32 {
+                table.bankroll + 1
+            }
+
+            fn choose_action(&mut self, _: &Hand, _: Card, _: &[Action]) -> Action {
+                Action::Stand
+            }
+        }
+
+        let mut game = Game::new(100, TableRules::default());
+
+        assert_eq!(game.play_round(&mut AllIn), Err(GameError::InvalidBet(101)));
+    }
+}
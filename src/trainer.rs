@@ -0,0 +1,99 @@
+//! # Trainer
+//!
+//! Keeps the count of the shoe for the `--count` mode, quizzes the player
+//! on the running count every few rounds, and keeps their score.
+
+use crate::score::Score;
+use blackjack::{Counter, CountingSystem, Event};
+
+/// Rounds played between two quizzes
+const QUIZ_EVERY: u16 = 3;
+
+/// Follows the count of the shoe and scores the player's answers
+pub struct Trainer {
+    counter: Counter,
+    score: Score,
+}
+
+impl Trainer {
+    /// Constructor: Trains `system` on a shoe of `decks` decks
+    pub fn new(system: CountingSystem, decks: u8) -> Self {
+        Trainer {
+            counter: Counter::new(system, decks),
+            score: Score::default(),
+        }
+    }
+
+    /// Returns the count of the shoe so far
+    pub fn counter(&self) -> &Counter {
+        &self.counter
+    }
+
+    /// Count the cards revealed by `events`
+    pub fn observe(&mut self, events: &[Event]) {
+        self.counter.observe(events);
+    }
+
+    /// Check if the player should be quizzed before round `round`
+    pub fn is_due(&self, round: u16) -> bool {
+        round > 0 && round.is_multiple_of(QUIZ_EVERY)
+    }
+
+    /// Score an answer for the running count. Returns true if it was right
+    pub fn check(&mut self, running_count: i32) -> bool {
+        self.score
+            .record(running_count == self.counter.running_count())
+    }
+
+    /// Right answers, out of the questions asked
+    pub fn score(&self) -> Score {
+        self.score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blackjack::{Seat, parse_cards};
+
+    /// Test the trainer's quizzes
+    ///
+    /// Feeds it dealt cards, checks a right and a wrong answer, and asserts the score
+    #[test]
+    fn test_trainer() {
+        let mut trainer = Trainer::new(CountingSystem::hi_lo(), 6);
+        assert!(!trainer.is_due(0));
+        assert!(trainer.is_due(QUIZ_EVERY));
+        assert_eq!(trainer.score().accuracy(), None);
+
+        let events: Vec<Event> = parse_cards("3C 6D TH")
+            .unwrap()
+            .into_iter()
+            .map(|card| Event::CardDealt {
+                to: Seat::Player(0),
+                card,
+            })
+            .collect();
+        trainer.observe(&events);
+
+        assert!(trainer.check(1));
+        assert!(!trainer.check(2));
+        assert_eq!(
+            trainer.score(),
+            Score {
+                correct: 1,
+                total: 2
+            }
+        );
+        assert_eq!(trainer.score().accuracy(), Some(50.0));
+
+        trainer.observe(&[
+            Event::RoundOver {
+                net: 0,
+                bankroll: 1000,
+            },
+            Event::Shuffled,
+        ]);
+        assert_eq!(trainer.counter().running_count(), 0);
+    }
+}
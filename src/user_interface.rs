@@ -3,12 +3,184 @@
 //! Methods from this module prompt, gain information from, and display information to the user.
 //! This module is meant to process and validate inputs, then pass them off.
 
-use crate::hand::Hand;
+use crate::{
+    coach::{self, Coach},
+    profile::{DEFAULT_PROFILE, Profile},
+    trainer::Trainer,
+};
+use blackjack::{
+    Action, ActionValues, BankrollReport, BetStrategy, Card, Event, Game, Hand, Offer, Phase,
+    RoundResult, Seat, SessionStats, Simulation, SimulationReport, Strategy, TableRules, TableView,
+};
 use colored::Colorize;
 use std::io::{self, Write};
 
-/// Displays the header with current bankroll
-pub fn display_header(i: u16, bankroll: u32) {
+/// The interactive player: every decision is prompted for at the terminal
+///
+/// With a `Coach`, `?` at a prompt shows the basic strategy play, and deviations from it are flagged.
+/// With `show_ev`, the exact expected value of each action is shown next to the hands.
+/// With a `Trainer`, the player is quizzed on the running count every few rounds.
+/// With a `BetStrategy`, bets are chosen by it instead of prompted for
+pub struct TerminalPlayer {
+    /// Name of the profile playing, shown in the header
+    pub profile: String,
+    pub coach: Option<Coach>,
+    pub show_ev: bool,
+    pub trainer: Option<Trainer>,
+    pub bets: Option<Box<dyn BetStrategy>>,
+}
+
+impl TerminalPlayer {
+    /// Constructor: Creates a terminal player for `profile`, coached if `coach` is given,
+    /// trained to count if `trainer` is given, and betting with `bets` if given
+    pub fn new(
+        profile: String,
+        coach: Option<Coach>,
+        show_ev: bool,
+        trainer: Option<Trainer>,
+        bets: Option<Box<dyn BetStrategy>>,
+    ) -> Self {
+        TerminalPlayer {
+            profile,
+            coach,
+            show_ev,
+            trainer,
+            bets,
+        }
+    }
+}
+
+impl Strategy for TerminalPlayer {
+    fn choose_bet(&mut self, table: &TableView) -> u32 {
+        display_header(
+            table.round,
+            table.bankroll,
+            table.rules,
+            table.seed,
+            Some(&self.profile),
+            self.coach.as_ref(),
+            self.trainer.as_ref(),
+        );
+        if let Some(trainer) = &mut self.trainer
+            && trainer.is_due(table.round)
+        {
+            quiz_count(trainer);
+        }
+
+        match &mut self.bets {
+            Some(bets) => {
+                let bet = table.clamp_bet(bets.bet(table));
+                println!("Enter your bet amount: ${} ({} betting)", bet, bets.name());
+                bet
+            }
+            None => get_bet(table.bankroll, table.rules),
+        }
+    }
+
+    fn choose_action(&mut self, hand: &Hand, dealer_upcard: Card, allowed: &[Action]) -> Action {
+        let Some(coach) = &mut self.coach else {
+            return get_action(allowed, None);
+        };
+
+        let recommended = coach.recommend(hand, dealer_upcard, allowed);
+        let advice = coach::advice(recommended, hand, dealer_upcard);
+        let action = get_action(allowed, Some(&advice));
+
+        if !coach.grade(action, recommended) {
+            print_deviation(&advice);
+        }
+        action
+    }
+
+    fn choose_offer(
+        &mut self,
+        offer: Offer,
+        hand: &Hand,
+        dealer_upcard: Card,
+        table: &TableView,
+    ) -> Action {
+        let Some(coach) = &mut self.coach else {
+            return get_offer_decision(offer, table.max_insurance, None);
+        };
+
+        let recommended = coach.recommend_offer(offer, hand, dealer_upcard);
+        let advice = coach::offer_advice(offer, recommended, hand, dealer_upcard);
+        let action = get_offer_decision(offer, table.max_insurance, Some(&advice));
+
+        if !coach.grade(action, recommended) {
+            print_deviation(&advice);
+        }
+        action
+    }
+
+    fn observe(&mut self, game: &Game, events: &[Event]) {
+        if let Some(trainer) = &mut self.trainer {
+            trainer.observe(events);
+        }
+        if let Some(bets) = &mut self.bets {
+            bets.observe(events);
+        }
+        show_events(game, events, self.show_ev);
+    }
+}
+
+/// Lets a bot play at the terminal: shows the table and announces each of the bot's decisions
+pub struct Spectator<S>(pub S);
+
+impl<S: Strategy> Strategy for Spectator<S> {
+    fn choose_bet(&mut self, table: &TableView) -> u32 {
+        display_header(
+            table.round,
+            table.bankroll,
+            table.rules,
+            table.seed,
+            None,
+            None,
+            None,
+        );
+
+        let bet = self.0.choose_bet(table);
+        println!("Enter your bet amount: ${}", bet);
+        bet
+    }
+
+    fn choose_action(&mut self, hand: &Hand, dealer_upcard: Card, allowed: &[Action]) -> Action {
+        let action = self.0.choose_action(hand, dealer_upcard, allowed);
+        println!("{}", format!("> {:?}", action).dimmed());
+        action
+    }
+
+    fn choose_offer(
+        &mut self,
+        offer: Offer,
+        hand: &Hand,
+        dealer_upcard: Card,
+        table: &TableView,
+    ) -> Action {
+        let action = self.0.choose_offer(offer, hand, dealer_upcard, table);
+        println!("{}", format!("{:?} offered > {:?}", offer, action).dimmed());
+        action
+    }
+
+    fn observe(&mut self, game: &Game, events: &[Event]) {
+        self.0.observe(game, events);
+        show_events(game, events, false);
+    }
+}
+
+/// Displays the header with current bankroll, a summary of the table rules, and the shuffle seed
+///
+/// With a profile, its name is shown above the bankroll. In coach mode, the session's accuracy score
+/// is shown as well, and in trainer mode the counting score
+pub fn display_header(
+    i: u16,
+    bankroll: u32,
+    rules: &TableRules,
+    seed: u64,
+    profile: Option<&str>,
+    coach: Option<&Coach>,
+    trainer: Option<&Trainer>,
+) {
     // \x1B[2J clears screen, \x1B[1;1H moves cursor to top-left
     print!("\x1B[2J\x1B[1;1H");
 
@@ -23,6 +195,11 @@ pub fn display_header(i: u16, bankroll: u32) {
     print!(" {:^28} ", title.cyan().bold());
     println!("{}", "│".cyan().bold());
     println!("{}", "├──────────────────────────────┤".cyan());
+    if let Some(profile) = profile {
+        print!("{}", "│".cyan().bold());
+        print!("   Player: {:<18} ", profile.yellow().bold());
+        println!("{}", "│".cyan().bold());
+    }
     print!("{}", "│".cyan().bold());
     print!(
         "   Current Bankroll: ${:<7} ",
@@ -30,14 +207,117 @@ pub fn display_header(i: u16, bankroll: u32) {
     );
     println!("{}", "│".cyan().bold());
     println!("{}", "└──────────────────────────────┘".cyan());
+    println!("{}", rules.to_string().dimmed());
+    println!(
+        "{}",
+        format!("Seed: {} (replay with --seed {})", seed, seed).dimmed()
+    );
+    if let Some(coach) = coach {
+        display_accuracy(coach);
+    }
+    if let Some(trainer) = trainer {
+        display_count_score(trainer);
+    }
     println!();
 }
 
-/// Prompts user for bet, then obtains and validates the bet
-pub fn get_bet(bankroll: u32) -> u32 {
+/// Displays the coach's accuracy score for the session
+fn display_accuracy(coach: &Coach) {
+    let score = coach.score();
+
+    match score.accuracy() {
+        Some(accuracy) => println!(
+            "{}",
+            format!(
+                "Coach: {}/{} decisions by the book ({:.0}%)",
+                score.correct, score.total, accuracy
+            )
+            .yellow()
+        ),
+        None => println!("{}", "Coach: press ? at any prompt for a hint".yellow()),
+    }
+}
+
+/// Displays the trainer's counting system and score for the session
+fn display_count_score(trainer: &Trainer) {
+    let score = trainer.score();
+    let system = &trainer.counter().system().name;
+
+    match score.accuracy() {
+        Some(accuracy) => println!(
+            "{}",
+            format!(
+                "Counting {}: {}/{} counts right ({:.0}%)",
+                system, score.correct, score.total, accuracy
+            )
+            .yellow()
+        ),
+        None => println!(
+            "{}",
+            format!(
+                "Counting {}: keep the running count, you'll be asked",
+                system
+            )
+            .yellow()
+        ),
+    }
+}
+
+/// Quiz the player on the running count, then show the running and true count
+pub fn quiz_count(trainer: &mut Trainer) {
+    let answer = loop {
+        let mut input = String::new();
+
+        print!("What's the running count? ");
+
+        io::stdout().flush().expect("Failed to flush stdout");
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read count");
+
+        match input.trim().trim_start_matches('+').parse::<i32>() {
+            Ok(count) => break count,
+            Err(_) => println!("That was not a number!"),
+        }
+    };
+
+    let counter = trainer.counter();
+    let count = format!(
+        "running count {:+}, true count {:+.1} ({:.1} decks left)",
+        counter.running_count(),
+        counter.true_count(),
+        counter.decks_remaining()
+    );
+
+    if trainer.check(answer) {
+        println!("{}\n", format!("✓ Right: {}", count).green().bold());
+    } else {
+        println!("{}\n", format!("✗ Wrong: {}", count).yellow().bold());
+    }
+}
+
+/// Flag a decision that deviated from basic strategy
+pub fn print_deviation(advice: &str) {
+    println!("{}", format!("✗ {}", advice).yellow().bold());
+}
+
+/// Print a hint, or complain about the `?` if there is no coach to give one
+fn print_hint(hint: Option<&str>) {
+    match hint {
+        Some(hint) => println!("{}", hint.yellow()),
+        None => println!("Invalid Input"),
+    }
+}
+
+/// Suffix for prompts that can show a hint
+fn hint_prompt(hint: Option<&str>) -> &'static str {
+    if hint.is_some() { ", ? for a hint" } else { "" }
+}
+
+/// Prompts user for bet, then obtains and validates the bet against bankroll and table limits
+pub fn get_bet(bankroll: u32, rules: &TableRules) -> u32 {
     loop {
         let mut input: String = String::new();
-        let bet: u32;
 
         print!("Enter your bet amount: $");
 
@@ -46,7 +326,7 @@ pub fn get_bet(bankroll: u32) -> u32 {
             .read_line(&mut input)
             .expect("Failed to read bet");
 
-        bet = match input.trim().parse() {
+        let bet: u32 = match input.trim().parse() {
             Ok(num) => num,
             Err(_) => {
                 println!("That was not a number!\n");
@@ -62,37 +342,306 @@ pub fn get_bet(bankroll: u32) -> u32 {
             println!("You can't play for free ;)\n");
             continue;
         }
+        if bet < rules.min_bet {
+            println!("Bad bet. Table minimum is ${}\n", rules.min_bet);
+            continue;
+        }
+        if bet > rules.max_bet {
+            println!("Bad bet. Table maximum is ${}\n", rules.max_bet);
+            continue;
+        }
 
         return bet;
     }
 }
 
+/// Announce that the cut card came out and the shoe was shuffled before this round
+pub fn print_shuffle() {
+    println!("\n{}", "Shuffling the shoe...".yellow().italic());
+}
+
+/// Show everything that happened since the last decision, then the hands if a decision is next
+///
+/// Dealer draws are printed one by one, and every settled hand gets its outcome box.
+/// With `show_ev`, the expected value of each action is shown under the hands
+pub fn show_events(game: &Game, events: &[Event], show_ev: bool) {
+    let split = game.player_hands.len() > 1;
+    let mut dealing = false;
+
+    for event in events {
+        match event {
+            Event::Shuffled => print_shuffle(),
+            Event::RoundStarted { .. } => {
+                dealing = true;
+                println!();
+            }
+            Event::Phase(Phase::DealerTurn) => {
+                dealing = false;
+                println!("\n\n=== Dealer's Turn ===\n");
+            }
+            Event::Phase(Phase::Settlement) => {
+                println!();
+                show_hands(game, false);
+            }
+            Event::Phase(_) | Event::Offered(_) => dealing = false,
+            Event::CardDealt {
+                to: Seat::Dealer,
+                card,
+            } if !dealing => println!("    Dealer draws [{}]", card),
+            Event::HoleCardRevealed(card) => println!("    Dealer reveals [{}]", card),
+            Event::InsuranceTaken(amount) => println!("\nInsurance: ${}", amount),
+            Event::EvenMoneyTaken => println!("\nYou take even money"),
+            Event::DealerPeeked { blackjack } => {
+                dealing = false;
+                println!("\nDealer checks for blackjack...");
+                if *blackjack {
+                    println!("Dealer has blackjack!");
+                }
+            }
+            Event::Doubled { bet, .. } => println!("\nDoubled down! Bet is now ${}", bet),
+            Event::Split { .. } => println!("\nSplit into {} hands", game.player_hands.len()),
+            Event::Surrendered { .. } => println!("\nYou surrender"),
+            Event::NextHand(hand) => println!("\nPlaying hand {}", hand + 1),
+            Event::InsuranceSettled { net } => print_insurance(*net > 0, net.unsigned_abs() as u32),
+            Event::HandSettled {
+                hand,
+                result,
+                bet,
+                net,
+            } => {
+                if split {
+                    println!("Hand {}:", hand + 1);
+                }
+                match result {
+                    RoundResult::PlayerWin => print_outcome(true, *net as u32),
+                    RoundResult::DealerWin => print_outcome(false, *bet),
+                    RoundResult::PlayerBlackjack => print_blackjack(*net as u32),
+                    RoundResult::Push => print_push(),
+                    RoundResult::Surrender => print_surrender((*bet as i64 + net) as u32),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if matches!(game.phase(), Phase::Dealing | Phase::PlayerTurn) {
+        println!();
+        show_hands(game, show_ev);
+    }
+}
+
 /// Show player and dealer hands
-pub fn show_hands(player_hand: &Hand, dealer_hand: &Hand) {
-    println!(
-        "Dealer: {} ({})    Player: {} ({})\n",
-        dealer_hand,
-        dealer_hand.value(),
-        player_hand,
-        player_hand.value()
-    );
+///
+/// After a split, each player hand is listed on its own line with its bet,
+/// and the hand being played is marked with `>`
+pub fn show_hands(game: &Game, show_ev: bool) {
+    let dealer = dealer_display(game);
+
+    if game.player_hands.len() == 1 {
+        let player_hand = game.player_hand();
+        println!(
+            "Dealer: {}    Player: {} ({})",
+            dealer,
+            player_hand,
+            player_hand.value()
+        );
+    } else {
+        println!("Dealer: {}", dealer);
+        for (n, player_hand) in game.player_hands.iter().enumerate() {
+            let marker = if n == game.active_hand { ">" } else { " " };
+            println!(
+                "{} Hand {}: {} ({})  ${}",
+                marker,
+                n + 1,
+                player_hand.hand,
+                player_hand.hand.value(),
+                player_hand.bet
+            );
+        }
+    }
+
+    if show_ev && let Some(values) = game.action_values() {
+        show_action_values(game, values);
+    }
+    println!();
+}
+
+/// Show the exact expected value of each action for the active hand, the best one highlighted
+fn show_action_values(game: &Game, values: ActionValues) {
+    let best = values.best(&game.allowed_actions());
+
+    let evs = [
+        (Action::Stand, "stand"),
+        (Action::Hit, "hit"),
+        (Action::Double, "double"),
+        (Action::Split, "split"),
+        (Action::Surrender, "surrender"),
+    ]
+    .into_iter()
+    .filter_map(|(action, name)| {
+        let ev = format!("{} {:+.1}%", name, 100.0 * values.ev(action)?);
+        Some(if action == best {
+            ev.green().bold().to_string()
+        } else {
+            ev.dimmed().to_string()
+        })
+    })
+    .collect::<Vec<_>>()
+    .join("  ");
+
+    println!("{} {}", "EV:".dimmed(), evs);
+}
+
+/// Dealer's cards and total, with the hole card shown as `[??]` while face down
+fn dealer_display(game: &Game) -> String {
+    let dealer_hand = &game.dealer_hand;
+
+    if game.hole_card_hidden() {
+        let upcard = dealer_hand.cards[0];
+        format!("[{}][{}] ({})", upcard, "??".dimmed(), upcard.value())
+    } else {
+        format!("{} ({})", dealer_hand, dealer_hand.value())
+    }
+}
+
+/// Prompt user for a decision on an offer (early surrender, even money or insurance)
+pub fn get_offer_decision(offer: Offer, max_insurance: u32, hint: Option<&str>) -> Action {
+    match offer {
+        Offer::EarlySurrender if early_surrender(hint) => Action::Surrender,
+        Offer::EvenMoney if take_even_money(hint) => Action::EvenMoney,
+        Offer::Insurance => match get_insurance(max_insurance, hint) {
+            0 => Action::Decline,
+            amount => Action::Insurance(amount),
+        },
+        _ => Action::Decline,
+    }
+}
+
+/// Prompt user to surrender before the dealer checks for blackjack, returns true for yes, false for no
+pub fn early_surrender(hint: Option<&str>) -> bool {
+    loop {
+        let mut input = String::new();
+
+        print!(
+            "Surrender before the dealer checks for blackjack? (y/n{}): ",
+            hint_prompt(hint)
+        );
+
+        io::stdout().flush().expect("Failed to flush stdout");
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read user decision");
+
+        match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            "?" => print_hint(hint),
+            _ => println!("Invalid Input"),
+        }
+    }
+}
+
+/// Prompt user for an insurance bet up to `max` after the dealer shows an ace. Returns 0 to decline
+pub fn get_insurance(max: u32, hint: Option<&str>) -> u32 {
+    loop {
+        let mut input = String::new();
+
+        print!(
+            "Dealer shows an Ace. Insurance up to ${} (0 to decline{}): $",
+            max,
+            hint_prompt(hint)
+        );
+
+        io::stdout().flush().expect("Failed to flush stdout");
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read insurance");
+
+        if input.trim() == "?" {
+            print_hint(hint);
+            continue;
+        }
+
+        match input.trim().parse::<u32>() {
+            Ok(amount) if amount <= max => return amount,
+            Ok(_) => println!("Insurance can be at most ${}\n", max),
+            Err(_) => println!("That was not a number!\n"),
+        }
+    }
+}
+
+/// Prompt user to take even money on their blackjack, returns true for yes, false for no
+pub fn take_even_money(hint: Option<&str>) -> bool {
+    loop {
+        let mut input = String::new();
+
+        print!(
+            "Dealer shows an Ace. Take even money? (y/n{}): ",
+            hint_prompt(hint)
+        );
+
+        io::stdout().flush().expect("Failed to flush stdout");
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read user decision");
+
+        match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            "?" => print_hint(hint),
+            _ => println!("Invalid Input"),
+        }
+    }
 }
 
-/// Prompt user for hit or stand decision. Returns true for hit, false for stand
-pub fn player_hits() -> bool {
+/// Prompt user for their next action, offering only the allowed ones
+pub fn get_action(allowed: &[Action], hint: Option<&str>) -> Action {
+    let prompt = allowed
+        .iter()
+        .filter_map(|action| match action {
+            Action::Hit => Some("(h)it"),
+            Action::Stand => Some("(s)tand"),
+            Action::Double => Some("(d)ouble"),
+            Action::Split => Some("s(p)lit"),
+            Action::Surrender => Some("su(r)render"),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
     loop {
         let mut input = String::new();
 
-        print!("Would you like to (h)it or (s)tand?: ");
+        let hint_prompt = if hint.is_some() {
+            " (? for a hint)"
+        } else {
+            ""
+        };
+        print!("Would you like to {}?{}: ", prompt, hint_prompt);
 
         io::stdout().flush().unwrap();
         io::stdin().read_line(&mut input).unwrap();
 
-        match input.trim().to_lowercase().as_str() {
-            "h" | "hit" => return true,
-            "s" | "stand" => return false,
-            _ => println!("\n**Invalid Input**\n"),
+        let action = match input.trim().to_lowercase().as_str() {
+            "h" | "hit" => Action::Hit,
+            "s" | "stand" => Action::Stand,
+            "d" | "double" => Action::Double,
+            "p" | "split" => Action::Split,
+            "r" | "surrender" => Action::Surrender,
+            "?" if hint.is_some() => {
+                print_hint(hint);
+                continue;
+            }
+            _ => {
+                println!("\n**Invalid Input**\n");
+                continue;
+            }
+        };
+
+        if allowed.contains(&action) {
+            return action;
         }
+        println!("\n**You can't do that right now**\n");
     }
 }
 
@@ -126,6 +675,72 @@ pub fn print_outcome(won: bool, amount: u32) {
     );
 }
 
+/// Print a summary box of `stats` under `title`, green if they finished up and red if down
+pub fn print_stats(title: &str, stats: &SessionStats) {
+    let colorize = |s: String| -> String {
+        if stats.net >= 0 {
+            s.green().to_string()
+        } else {
+            s.red().to_string()
+        }
+    };
+    let net = match stats.net {
+        0 => "$0".to_string(),
+        net if net > 0 => format!("+${}", net),
+        net => format!("-${}", net.unsigned_abs()),
+    };
+    let rows = [
+        ("Rounds played", stats.rounds.to_string()),
+        ("Hands played", stats.hands.to_string()),
+        ("Wins", stats.wins.to_string()),
+        ("Blackjacks", stats.blackjacks.to_string()),
+        ("Losses", stats.losses.to_string()),
+        ("Busts", stats.busts.to_string()),
+        ("Pushes", stats.pushes.to_string()),
+        ("Surrenders", stats.surrenders.to_string()),
+        ("Doubles", stats.doubles.to_string()),
+        ("Splits", stats.splits.to_string()),
+        ("Biggest win", format!("${}", stats.biggest_win)),
+        ("Biggest loss", format!("${}", stats.biggest_loss)),
+        ("Win streak", stats.longest_win_streak.to_string()),
+        ("Loss streak", stats.longest_loss_streak.to_string()),
+    ];
+
+    println!(
+        "\n{}",
+        colorize("┌──────────────────────────────┐".to_string())
+    );
+    println!("{}", colorize(format!("│ {:^28} │", title)));
+    println!(
+        "{}",
+        colorize("├──────────────────────────────┤".to_string())
+    );
+    for (label, value) in rows {
+        println!("{}", colorize(format!("│ {:<16}{:>12} │", label, value)));
+    }
+    println!(
+        "{}",
+        colorize("├──────────────────────────────┤".to_string())
+    );
+    println!(
+        "{}",
+        colorize(format!("│ {:<16}{:>12} │", "Net result", net))
+    );
+    println!(
+        "{}",
+        colorize("└──────────────────────────────┘".to_string())
+    );
+}
+
+/// Print insurance result, either won (with payout) or lost (with amount)
+pub fn print_insurance(won: bool, amount: u32) {
+    if won {
+        println!("{}", format!("Insurance pays ${}", amount).green());
+    } else {
+        println!("{}", format!("Insurance lost: ${}", amount).red());
+    }
+}
+
 /// Print push message
 pub fn print_push() {
     let msg: &str = "   PUSH   ";
@@ -140,7 +755,31 @@ pub fn print_push() {
     let msg_line = format!("│          {:<12}        │", msg);
     println!("{}", colorize(msg_line));
 
-    let money_line = format!("│          MONEY BACK          │");
+    let money_line = "│          MONEY BACK          │".to_string();
+    println!("{}", colorize(money_line));
+
+    println!(
+        "{}",
+        colorize("└──────────────────────────────┘".to_string())
+    );
+}
+
+/// Print surrender message with the amount refunded
+pub fn print_surrender(refund: u32) {
+    let msg: &str = "SURRENDER";
+
+    let colorize = |s: String| -> String { s.yellow().to_string() };
+
+    println!(
+        "{}",
+        colorize("┌──────────────────────────────┐".to_string())
+    );
+
+    let msg_line = format!("│          {:<12}        │", msg);
+    println!("{}", colorize(msg_line));
+
+    let label = "Refund";
+    let money_line = format!("│        {}: ${:<5}        │", label, refund);
     println!("{}", colorize(money_line));
 
     println!(
@@ -173,10 +812,102 @@ pub fn print_blackjack(amount: u32) {
     );
 }
 
+/// Print a one-line summary of a saved session
+fn print_saved_session(saved: &Game) {
+    println!(
+        "{}",
+        format!(
+            "Saved session: ${} after {} rounds ({})",
+            saved.bankroll, saved.i, saved.rules
+        )
+        .cyan()
+    );
+}
+
+/// Offer to resume a saved session, returns true for yes, false to start a new one
+pub fn resume_session(saved: &Game) -> bool {
+    print_saved_session(saved);
+
+    loop {
+        let mut input = String::new();
+
+        print!("Resume it? (y/n): ");
+
+        io::stdout().flush().expect("Failed to flush stdout");
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read user decision");
+
+        match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => println!("Invalid Input"),
+        }
+    }
+}
+
+/// Tell the player the saved session isn't resumed because a new table was asked for
+pub fn print_save_skipped(saved: &Game) {
+    print_saved_session(saved);
+    println!(
+        "{}",
+        "Not resuming it, since --rules or --seed was given: your bankroll moves to the new table\n"
+            .yellow()
+    );
+}
+
+/// Ask which profile to play as, listing the ones played before. Enter picks the default profile
+pub fn choose_profile(names: &[String]) -> String {
+    println!("{}", format!("Profiles: {}", names.join(", ")).cyan());
+
+    loop {
+        let mut input = String::new();
+
+        print!("Profile name (Enter for {}): ", DEFAULT_PROFILE);
+
+        io::stdout().flush().expect("Failed to flush stdout");
+        io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read user decision");
+
+        match input.trim() {
+            "" => return DEFAULT_PROFILE.to_string(),
+            name if Profile::is_valid_name(name) => return name.to_string(),
+            _ => println!("Invalid Input: up to 16 letters, digits, - or _"),
+        }
+    }
+}
+
+/// Print a recorded action the way `Spectator` shows a bot's choices
+pub fn print_recorded_action(action: Action) {
+    match action {
+        Action::Bet(bet) => println!("Enter your bet amount: ${}", bet),
+        _ => println!("{}", format!("> {:?}", action).dimmed()),
+    }
+}
+
+/// Wait for the user before the next step of a replay, returns false if they quit
+///
+/// Keeps going without asking once stdin is closed, so a replay can be piped
+pub fn next_step() -> bool {
+    let mut input = String::new();
+
+    print!("{}", "[Enter] next step, (q)uit: ".dimmed());
+
+    io::stdout().flush().expect("Failed to flush stdout");
+    io::stdin()
+        .read_line(&mut input)
+        .expect("Failed to read user decision");
+
+    !matches!(input.trim().to_lowercase().as_str(), "q" | "quit")
+}
+
 /// Prompt user to play again, returns true for yes, false for no
-pub fn play_again(bankroll: u32) -> bool {
+///
+/// Returns false without asking if the bankroll can no longer cover the table minimum
+pub fn play_again(bankroll: u32, min_bet: u32) -> bool {
     loop {
-        if bankroll == 0 {
+        if bankroll == 0 || bankroll < min_bet {
             println!(
                 "{}",
                 "\n\nYou are out of money! You are not useful to us anymore.\n\n"
@@ -202,3 +933,87 @@ pub fn play_again(bankroll: u32) -> bool {
         }
     }
 }
+
+/// Print the results of a simulation: expected value with its confidence interval, and how hands ended
+pub fn print_report(simulation: &Simulation, player: &str, report: &SimulationReport) {
+    let (low, high) = report.net.confidence_interval();
+
+    println!("{}", "=== Simulation ===".cyan().bold());
+    println!("Player:         {}", player);
+    println!("Rules:          {}", simulation.rules);
+    println!("Rounds:         {}", report.rounds());
+    println!("Seed:           {}", simulation.seed);
+    println!();
+
+    let ev = format!("{:+.3}%", 100.0 * report.ev());
+    let ev = if report.ev() < 0.0 {
+        ev.red()
+    } else {
+        ev.green()
+    };
+    println!("EV per hand:    {} of the bet", ev.bold());
+    println!(
+        "95% CI:         {:+.3}% to {:+.3}%",
+        100.0 * low,
+        100.0 * high
+    );
+    println!("Std deviation:  {:.3} bets", report.net.std_dev());
+    println!();
+
+    println!("Hands settled:  {}", report.hands);
+    for (label, count) in [
+        ("Wins", report.wins),
+        ("Blackjacks", report.blackjacks),
+        ("Pushes", report.pushes),
+        ("Losses", report.losses),
+        ("Surrenders", report.surrenders),
+    ] {
+        println!("  {:<12} {:>6.2}%", label, 100.0 * report.frequency(count));
+    }
+}
+
+/// Prints the results of playing sessions with each betting system, one line per system
+pub fn print_bankroll_reports(
+    simulation: &Simulation,
+    player: &str,
+    reports: &[(&str, BankrollReport)],
+) {
+    let Some((_, first)) = reports.first() else {
+        return;
+    };
+
+    println!("{}", "=== Betting Systems ===".cyan().bold());
+    println!("Player:         {}", player);
+    println!("Rules:          {}", simulation.rules);
+    println!(
+        "Sessions:       {} of up to {} rounds from ${}",
+        first.sessions(),
+        simulation.rounds,
+        first.bankroll
+    );
+    println!("Seed:           {}", simulation.seed);
+    println!();
+
+    println!(
+        "{:<12} {:>12} {:>10} {:>14} {:>10} {:>8}",
+        "System", "Risk of ruin", "Growth", "Final bankroll", "Avg bet", "Rounds"
+    );
+    for (name, report) in reports {
+        let growth = format!("{:+.1}%", 100.0 * report.growth());
+        let growth = if report.growth() < 0.0 {
+            growth.red()
+        } else {
+            growth.green()
+        };
+
+        println!(
+            "{:<12} {:>11.1}% {:>10} {:>14} {:>10} {:>8.0}",
+            name,
+            100.0 * report.risk_of_ruin(),
+            growth,
+            format!("${:.0}", report.final_bankroll.mean()),
+            format!("${:.0}", report.average_bet.mean()),
+            report.rounds.mean()
+        );
+    }
+}